@@ -0,0 +1,21 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use terminal_color_builder::OutputFormatter;
+
+fn single_segment(c: &mut Criterion) {
+    let formatter = OutputFormatter::new().fg().red().bg().black().bold().text_str("a single colored string");
+    c.bench_function("print single segment", |b| b.iter(|| black_box(&formatter).print()));
+}
+
+fn multi_segment(c: &mut Criterion) {
+    let formatter = OutputFormatter::new()
+        .fg().red().text_str("error: ")
+        .fg().yellow().bold().text_str("src/lib.rs")
+        .reset()
+        .text_str(" failed to compile, ")
+        .fg().green().text_str("1 warning");
+    c.bench_function("print multi segment", |b| b.iter(|| black_box(&formatter).print()));
+}
+
+criterion_group!(benches, single_segment, multi_segment);
+criterion_main!(benches);
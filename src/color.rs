@@ -1,4 +1,12 @@
-#[derive(Debug)]
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::style::Style;
+use core::convert::TryFrom;
+use core::fmt;
+
+#[derive(Clone)]
 pub enum COLORS {
     Black,
     Red,
@@ -10,12 +18,201 @@ pub enum COLORS {
     White,
     Default,
     None,
+    /// no color in the terminal like `None`, renderers that know transparency (e.g. HTML) make
+    /// the color explicitly transparent instead of inheriting it
+    Transparent,
+    /// an entry of the 256-color palette
+    Ansi256(u8),
+    #[cfg(feature = "truecolor")]
     HEX(String),
+    #[cfg(feature = "truecolor")]
+    Rgb(u8, u8, u8),
+}
+
+/// a color as its red, green and blue channels
+pub type RgbValue = (u8, u8, u8);
+
+impl COLORS {
+    /// the rgb value of a hex or rgb color, `None` for every other color and for invalid hex values
+    #[cfg(feature = "truecolor")]
+    fn truecolor_rgb(&self) -> Option<(u8, u8, u8)> {
+        match self {
+            COLORS::HEX(hex) => match Color::parse_hex(hex) {
+                Ok(COLORS::Rgb(r, g, b)) => Some((r, g, b)),
+                _ => None,
+            },
+            COLORS::Rgb(r, g, b) => Some((*r, *g, *b)),
+            _ => None,
+        }
+    }
+
+    /// the lowercase name of a base color or `Default`, `None` for every other color
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// assert_eq!(Some("red"), COLORS::Red.ansi16_name());
+    /// assert_eq!(None, COLORS::Ansi256(196).ansi16_name());
+    /// ```
+    pub fn ansi16_name(&self) -> Option<&'static str> {
+        match self {
+            COLORS::Black => Some("black"),
+            COLORS::Red => Some("red"),
+            COLORS::Green => Some("green"),
+            COLORS::Yellow => Some("yellow"),
+            COLORS::Blue => Some("blue"),
+            COLORS::Magenta => Some("magenta"),
+            COLORS::Cyan => Some("cyan"),
+            COLORS::White => Some("white"),
+            COLORS::Default => Some("default"),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            COLORS::Black => "Black",
+            COLORS::Red => "Red",
+            COLORS::Green => "Green",
+            COLORS::Yellow => "Yellow",
+            COLORS::Blue => "Blue",
+            COLORS::Magenta => "Magenta",
+            COLORS::Cyan => "Cyan",
+            COLORS::White => "White",
+            COLORS::Default => "Default",
+            COLORS::None => "None",
+            COLORS::Transparent => "Transparent",
+            COLORS::Ansi256(_) => "Ansi256",
+            #[cfg(feature = "truecolor")]
+            COLORS::HEX(_) => "HEX",
+            #[cfg(feature = "truecolor")]
+            COLORS::Rgb(..) => "Rgb",
+        }
+    }
+}
+
+/// hex and rgb colors are equal if they resolve to the same rgb value
+///
+/// Example
+/// ```
+/// use terminal_color_builder::color::*;
+/// # #[cfg(feature = "truecolor")]
+/// assert_eq!(COLORS::HEX("#f00".to_string()), COLORS::Rgb(255, 0, 0));
+/// ```
+impl PartialEq for COLORS {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            #[cfg(feature = "truecolor")]
+            (COLORS::HEX(a), COLORS::HEX(b)) if a == b => true,
+            #[cfg(feature = "truecolor")]
+            (COLORS::HEX(_) | COLORS::Rgb(..), COLORS::HEX(_) | COLORS::Rgb(..)) => {
+                self.truecolor_rgb().is_some() && self.truecolor_rgb() == other.truecolor_rgb()
+            },
+            (COLORS::Ansi256(a), COLORS::Ansi256(b)) => a == b,
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+/// hex colors show the rgb value they resolve to next to the hex string
+impl fmt::Debug for COLORS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "truecolor")]
+            COLORS::HEX(hex) => match self.truecolor_rgb() {
+                Some(rgb) => f.debug_tuple("HEX").field(hex).field(&rgb).finish(),
+                None => f.debug_tuple("HEX").field(hex).finish(),
+            },
+            #[cfg(feature = "truecolor")]
+            COLORS::Rgb(r, g, b) => f.debug_tuple("Rgb").field(r).field(g).field(b).finish(),
+            COLORS::Ansi256(n) => f.debug_tuple("Ansi256").field(n).finish(),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+/// the name of the color, hex and rgb colors are shown as their `(r, g, b)` value
+///
+/// Example
+/// ```
+/// use terminal_color_builder::color::*;
+/// assert_eq!("Red", COLORS::Red.to_string());
+/// # #[cfg(feature = "truecolor")]
+/// assert_eq!("(255, 255, 255)", COLORS::HEX("#fff".to_string()).to_string());
+/// ```
+impl fmt::Display for COLORS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "truecolor")]
+        if let Some((r, g, b)) = self.truecolor_rgb() {
+            return write!(f, "({}, {}, {})", r, g, b);
+        }
+        match self {
+            COLORS::Ansi256(n) => write!(f, "Ansi256({})", n),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+/// the rgb values of the 16 standard colors from the xterm palette,
+/// the eight base colors black to white followed by their bright variants
+const ANSI16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (128, 0, 0),
+    (0, 128, 0),
+    (128, 128, 0),
+    (0, 0, 128),
+    (128, 0, 128),
+    (0, 128, 128),
+    (192, 192, 192),
+    (128, 128, 128),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (0, 0, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// the levels of a channel in the 6x6x6 color cube of the 256-color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// the levels of a channel in the 4x4x4 color cube of the 88-color palette
+const ANSI88_CUBE_LEVELS: [u8; 4] = [0, 139, 205, 255];
+
+/// the gray ramp at the end of the 88-color palette
+const ANSI88_GRAYS: [u8; 8] = [46, 92, 115, 139, 162, 185, 208, 231];
+
+/// The reasons a hex color cannot be parsed
+#[cfg(feature = "truecolor")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum HexError {
+    /// there are no digits, e.g. `#` or a blank string
+    Empty,
+    /// the number of digits is neither 3 nor 6
+    InvalidLength(usize),
+    /// a character that is not a hex digit
+    InvalidDigit(char),
+}
+
+#[cfg(feature = "truecolor")]
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexError::Empty => write!(f, "Empty Hex Value"),
+            HexError::InvalidLength(len) => write!(f, "Incorrect Hex Value: expected 3 or 6 digits, got {}", len),
+            HexError::InvalidDigit(c) => write!(f, "Not a valid hex-value: {:?}", c),
+        }
+    }
 }
 
+#[cfg(all(feature = "std", feature = "truecolor"))]
+impl std::error::Error for HexError {}
+
 pub struct Color {
     fg: COLORS,
     bg: COLORS,
+    styles: Vec<Style>,
 }
 
 /// Create a single color object
@@ -32,6 +229,24 @@ impl Color {
         Color {
             fg,
             bg,
+            styles: vec![],
+        }
+    }
+
+    /// create a color that also applies styles like bold or underline
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// use terminal_color_builder::style::Style;
+    /// let c = Color::with_styles(COLORS::Green, COLORS::None, &[Style::Bold]);
+    /// assert_eq!("\u{1b}[32;1mhi\u{1b}[39;49;22m", c.print("hi".to_string()));
+    /// ```
+    pub fn with_styles(fg: COLORS, bg: COLORS, styles: &[Style]) -> Self {
+        Color {
+            fg,
+            bg,
+            styles: styles.to_vec(),
         }
     }
 
@@ -40,28 +255,37 @@ impl Color {
         format!("{}{}{}", self.set(), message, self.unset())
     }
 
+    /// write a message in a custom color to a writer, without building the colored string first
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// let mut out: Vec<u8> = vec![];
+    /// Color::new(COLORS::Green, COLORS::None).write(&mut out, "hello").unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write<W: std::io::Write>(&self, w: &mut W, message: &str) -> std::io::Result<()> {
+        Color::write_codes(w, &self.apply())?;
+        w.write_all(message.as_bytes())?;
+        Color::write_codes(w, &self.unapply())
+    }
+
     /// parses the colors, but returns vector for later formatting
     /// @internal
     pub fn apply(&self) -> Vec<String> {
         let mut sets = Vec::new();
-        let fg = Color::parse_color(&self.fg);
-        if fg != "".to_string() {
-            sets.push(format!("3{}", fg));
-        }
-        let bg = Color::parse_color(&self.bg);
-        if bg != "".to_string() {
-            sets.push(format!("4{}", bg))
-        }
+        sets.extend(Color::sgr_code(&self.fg, false));
+        sets.extend(Color::sgr_code(&self.bg, true));
+        sets.extend(self.styles.iter().map(Style::on_code));
         sets
     }
 
     /// resets colors, but returns vector for later formatting
     /// @internal
     pub fn unapply(&self) -> Vec<String> {
-        let mut sets = Vec::new();
-        sets.push("39".to_string());
-        sets.push("49".to_string());
-        sets
+        let mut unsets = vec!["39".to_string(), "49".to_string()];
+        unsets.extend(Style::off_codes(&self.styles));
+        unsets
     }
 
     /// sets the colors
@@ -76,78 +300,570 @@ impl Color {
         Color::format(sets)
     }
 
+    /// the escape sequence resetting the foreground to the terminal default
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// let red = Color::new(COLORS::Red, COLORS::None).set();
+    /// println!("{}error{} details", red, Color::reset_fg());
+    /// ```
+    pub fn reset_fg() -> String {
+        Color::format(&["39".to_string()])
+    }
+
+    /// the escape sequence resetting the background to the terminal default
+    pub fn reset_bg() -> String {
+        Color::format(&["49".to_string()])
+    }
+
+    /// the escape sequence resetting all colors and styles
+    pub fn reset_all() -> String {
+        Color::format(&["0".to_string()])
+    }
+
     /// concatenates a set of colors
-    pub fn format(m: &Vec<String>) -> String {
-        format!("\x1b[{}m", m.join(";"))
+    pub fn format(m: &[String]) -> String {
+        let mut formatted = String::new();
+        Color::format_into(&mut formatted, m);
+        formatted
+    }
+
+    /// appends the escape sequence for a set of colors to a buffer, without allocating a new string
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// let mut buf = String::from("> ");
+    /// Color::format_into(&mut buf, &["31".to_string(), "1".to_string()]);
+    /// assert_eq!("> \u{1b}[31;1m", buf);
+    /// ```
+    pub fn format_into(buf: &mut String, m: &[String]) {
+        buf.push_str("\x1b[");
+        for (i, code) in m.iter().enumerate() {
+            if i > 0 {
+                buf.push(';');
+            }
+            buf.push_str(code);
+        }
+        buf.push('m');
     }
 
-    fn parse_color(color: &COLORS) -> String {
+    /// the WCAG contrast ratio between two colors, from 1 (no contrast) to 21 (black on white)
+    /// colors without a fixed rgb value (`Default`, `None`) are treated as black
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// let ratio = Color::contrast_ratio(&COLORS::Black, &COLORS::White);
+    /// assert!(ratio > 10.0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn contrast_ratio(a: &COLORS, b: &COLORS) -> f32 {
+        let la = Color::relative_luminance(a);
+        let lb = Color::relative_luminance(b);
+        (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+    }
+
+    /// picks black or white, whichever is better readable on the given background
+    #[cfg(feature = "std")]
+    pub fn best_text_for(bg: &COLORS) -> COLORS {
+        if Color::contrast_ratio(&COLORS::White, bg) >= Color::contrast_ratio(&COLORS::Black, bg) {
+            COLORS::White
+        } else {
+            COLORS::Black
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn relative_luminance(color: &COLORS) -> f32 {
+        let (r, g, b) = Color::to_rgb(color).unwrap_or((0, 0, 0));
+        let channel = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+    }
+
+    /// the perceptual distance between two colors, with the "redmean" weighting of the channels
+    /// named colors are resolved to rgb first, colors without an rgb value are infinitely far away
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// let orange = COLORS::Ansi256(208);
+    /// assert!(Color::distance(&orange, &COLORS::Yellow) < Color::distance(&orange, &COLORS::Blue));
+    /// assert_eq!(0.0, Color::distance(&COLORS::Red, &COLORS::Red));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn distance(a: &COLORS, b: &COLORS) -> f32 {
+        match (Color::to_rgb(a), Color::to_rgb(b)) {
+            (Some(a), Some(b)) => Color::redmean_squared(a, b).sqrt(),
+            _ => f32::INFINITY,
+        }
+    }
+
+    /// the squared "redmean" distance, enough to compare distances without a square root
+    fn redmean_squared(a: RgbValue, b: RgbValue) -> f32 {
+        let mean_r = (a.0 as f32 + b.0 as f32) / 2.0;
+        let d = |x: u8, y: u8| (x as f32 - y as f32) * (x as f32 - y as f32);
+        (2.0 + mean_r / 256.0) * d(a.0, b.0) + 4.0 * d(a.1, b.1) + (2.0 + (255.0 - mean_r) / 256.0) * d(a.2, b.2)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_codes<W: std::io::Write>(w: &mut W, codes: &[String]) -> std::io::Result<()> {
+        w.write_all(b"\x1b[")?;
+        for (i, code) in codes.iter().enumerate() {
+            if i > 0 {
+                w.write_all(b";")?;
+            }
+            w.write_all(code.as_bytes())?;
+        }
+        w.write_all(b"m")
+    }
+
+    /// the rgb values of foreground and background, `None` for `COLORS::None` and `COLORS::Default`
+    /// named colors resolve to the standard xterm palette
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// let c = Color::new(COLORS::Red, COLORS::None);
+    /// assert_eq!((Some((128, 0, 0)), None), c.resolve());
+    /// ```
+    pub fn resolve(&self) -> (Option<RgbValue>, Option<RgbValue>) {
+        (Color::to_rgb(&self.fg), Color::to_rgb(&self.bg))
+    }
+
+    /// the lowercase name of the foreground if it is a base color or `Default`, see `COLORS::ansi16_name`
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// assert_eq!(Some("green"), Color::new(COLORS::Green, COLORS::Red).ansi16_name());
+    /// ```
+    pub fn ansi16_name(&self) -> Option<&'static str> {
+        self.fg.ansi16_name()
+    }
+
+    /// the css colors (`#rrggbb`) of foreground and background, `None` for `COLORS::None` and
+    /// `COLORS::Default` and `transparent` for `COLORS::Transparent`
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// let c = Color::new(COLORS::Green, COLORS::None);
+    /// assert_eq!((Some("#008000".to_string()), None), c.to_css());
+    /// ```
+    pub fn to_css(&self) -> (Option<String>, Option<String>) {
+        (Color::css_color(&self.fg), Color::css_color(&self.bg))
+    }
+
+    /// the css value of a single color, see `to_css`
+    fn css_color(color: &COLORS) -> Option<String> {
         match color {
-            COLORS::Black => "0".to_string(),
-            COLORS::Red => "1".to_string(),
-            COLORS::Green => "2".to_string(),
-            COLORS::Yellow => "3".to_string(),
-            COLORS::Blue => "4".to_string(),
-            COLORS::Magenta => "5".to_string(),
-            COLORS::Cyan => "6".to_string(),
-            COLORS::White => "7".to_string(),
-            COLORS::Default => "9".to_string(),
-            COLORS::HEX(hex) => Color::convert_hex_to_ansi(Color::string_to_hexdec(hex)),
-            COLORS::None => "".to_string(),
-        }
-    }
-
-    fn string_to_hexdec<'a>(hex: &'a String) -> u32 {
-        let mut hex_vec: Vec<&'a str> = hex.split("").collect();
-        hex_vec.retain(|&x| x != "" && x != "#");
-
-        let mut return_vex: Vec<&'a str> = vec![];
-        match hex_vec.len() {
-            3 => {
-                for item in &hex_vec {
-                    return_vex.extend_from_slice(&[*item, *item]);
-                }
-            },
-            6 => return_vex.extend(&hex_vec),
-            _ => panic!("Incorrect Hex Value"),
+            COLORS::Transparent => Some("transparent".to_string()),
+            _ => Color::to_rgb(color).map(|(r, g, b)| format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        }
+    }
+
+    /// the color `fg` looks like with the given opacity on top of `bg`, alpha is clamped to 0 - 1
+    /// if one of the colors has no rgb value, the other one is returned
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// let pink = Color::over(&COLORS::Rgb(255, 0, 0), &COLORS::Rgb(255, 255, 255), 0.5);
+    /// assert_eq!(COLORS::Rgb(255, 128, 128), pink);
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn over(fg: &COLORS, bg: &COLORS, alpha: f32) -> COLORS {
+        let (front, back) = match (Color::to_rgb(fg), Color::to_rgb(bg)) {
+            (Some(front), Some(back)) => (front, back),
+            (None, _) => return bg.clone(),
+            (_, None) => return fg.clone(),
         };
-        let mut hex_u: Vec<u32> = vec![];
-        for item in &return_vex {
-            let str: String = item.to_uppercase();
-            let m: u32 = match str.as_str() {
-                "A" => 10,
-                "B" => 11,
-                "C" => 12,
-                "D" => 13,
-                "E" => 14,
-                "F" => 15,
-                "1" => 1,
-                "2" => 2,
-                "3" => 3,
-                "4" => 4,
-                "5" => 5,
-                "6" => 6,
-                "7" => 7,
-                "8" => 8,
-                "9" => 9,
-                "0" => 0,
-                _ => panic!("Not a valid hex-value"),
+        let alpha = alpha.clamp(0.0, 1.0);
+        let channel = |f: u8, b: u8| (f as f32 * alpha + b as f32 * (1.0 - alpha) + 0.5) as u8;
+        COLORS::Rgb(channel(front.0, back.0), channel(front.1, back.1), channel(front.2, back.2))
+    }
+
+    /// the negative of a color, every channel is turned into `255 - c`
+    /// named colors are resolved to their rgb value first, colors without one are returned as they are
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// assert_eq!(COLORS::Rgb(255, 255, 255), Color::invert_rgb(&COLORS::Black));
+    /// assert_eq!(COLORS::Rgb(245, 235, 225), Color::invert_rgb(&COLORS::Rgb(10, 20, 30)));
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn invert_rgb(color: &COLORS) -> COLORS {
+        match Color::to_rgb(color) {
+            Some((r, g, b)) => COLORS::Rgb(255 - r, 255 - g, 255 - b),
+            None => color.clone(),
+        }
+    }
+
+    /// the OSC 8 sequence starting a hyperlink, an empty url ends the hyperlink
+    pub fn link(url: &str) -> String {
+        format!("\x1b]8;;{}\x1b\\", url)
+    }
+
+    /// checks if an SGR code sets the foreground
+    /// @internal
+    pub(crate) fn is_fg_code(code: &str) -> bool {
+        (code.len() == 2 && code.starts_with('3')) || code.starts_with("38;")
+    }
+
+    /// checks if an SGR code sets the background
+    /// @internal
+    pub(crate) fn is_bg_code(code: &str) -> bool {
+        (code.len() == 2 && code.starts_with('4')) || code.starts_with("48;")
+    }
+
+    /// the SGR code setting the color as foreground (`3x`, `38;...`) or background (`4x`, `48;...`)
+    fn sgr_code(color: &COLORS, background: bool) -> Option<String> {
+        let scope = if background { '4' } else { '3' };
+        let base = |c: u8| Some(format!("{}{}", scope, c));
+        match color {
+            COLORS::Black => base(0),
+            COLORS::Red => base(1),
+            COLORS::Green => base(2),
+            COLORS::Yellow => base(3),
+            COLORS::Blue => base(4),
+            COLORS::Magenta => base(5),
+            COLORS::Cyan => base(6),
+            COLORS::White => base(7),
+            COLORS::Default => base(9),
+            // extended colors are selected with 38 or 48 followed by their own parameters
+            COLORS::Ansi256(n) => Some(format!("{}8;5;{}", scope, n)),
+            #[cfg(feature = "truecolor")]
+            COLORS::HEX(hex) => Some(format!("{}{}", scope, Color::convert_hex_to_ansi(Color::string_to_hexdec(hex)))),
+            #[cfg(feature = "truecolor")]
+            COLORS::Rgb(r, g, b) => Some(format!("{}8;2;{};{};{}", scope, r, g, b)),
+            COLORS::None | COLORS::Transparent => None,
+        }
+    }
+
+    /// turns a single fg or bg SGR code back into the color it sets
+    /// @internal
+    pub(crate) fn color_from_code(code: &str) -> Option<COLORS> {
+        if !Color::is_fg_code(code) && !Color::is_bg_code(code) {
+            return None;
+        }
+        let params: Vec<&str> = code[1..].split(';').collect();
+        match params.as_slice() {
+            ["0"] => Some(COLORS::Black),
+            ["1"] => Some(COLORS::Red),
+            ["2"] => Some(COLORS::Green),
+            ["3"] => Some(COLORS::Yellow),
+            ["4"] => Some(COLORS::Blue),
+            ["5"] => Some(COLORS::Magenta),
+            ["6"] => Some(COLORS::Cyan),
+            ["7"] => Some(COLORS::White),
+            ["9"] => Some(COLORS::Default),
+            #[cfg(feature = "truecolor")]
+            ["8", "2", r, g, b] => Some(COLORS::Rgb(r.parse().ok()?, g.parse().ok()?, b.parse().ok()?)),
+            ["8", "5", n] => Some(COLORS::Ansi256(n.parse().ok()?)),
+            _ => None,
+        }
+    }
+
+    /// the foreground, background and styles a list of SGR parameters sets, the inverse of `apply`
+    /// colors that are not set are `COLORS::None`, bright colors are returned as their
+    /// 256-color palette entry, unknown parameters are ignored and an incomplete extended color
+    /// ends the list
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// use terminal_color_builder::style::Style;
+    /// let (fg, bg, styles) = Color::parse_sgr(&[1, 31, 48, 5, 208]);
+    /// assert_eq!((COLORS::Red, COLORS::Ansi256(208)), (fg, bg));
+    /// assert_eq!(vec![Style::Bold], styles);
+    /// ```
+    pub fn parse_sgr(codes: &[u16]) -> (COLORS, COLORS, Vec<Style>) {
+        let (mut fg, mut bg) = (COLORS::None, COLORS::None);
+        let mut styles: Vec<Style> = vec![];
+        let mut i = 0;
+        while i < codes.len() {
+            let code = codes[i];
+            i += 1;
+            let color = match code {
+                0 => {
+                    fg = COLORS::None;
+                    bg = COLORS::None;
+                    styles.clear();
+                    continue;
+                },
+                30..=37 | 40..=47 => Some(Color::base_color((code % 10) as usize)),
+                39 | 49 => Some(COLORS::Default),
+                90..=97 | 100..=107 => Some(COLORS::Ansi256(8 + (code % 10) as u8)),
+                // extended colors take their parameters from the following codes
+                38 | 48 => match codes[i..] {
+                    [5, n, ..] => {
+                        i += 2;
+                        u8::try_from(n).ok().map(COLORS::Ansi256)
+                    },
+                    [2, r, g, b, ..] => {
+                        i += 4;
+                        match (u8::try_from(r), u8::try_from(g), u8::try_from(b)) {
+                            #[cfg(feature = "truecolor")]
+                            (Ok(r), Ok(g), Ok(b)) => Some(COLORS::Rgb(r, g, b)),
+                            #[cfg(not(feature = "truecolor"))]
+                            (Ok(r), Ok(g), Ok(b)) => Some(COLORS::Ansi256(Color::rgb_to_ansi256(r, g, b))),
+                            _ => None,
+                        }
+                    },
+                    // the rest of the list cannot be told apart from the broken color
+                    _ => {
+                        i = codes.len();
+                        None
+                    },
+                },
+                _ => {
+                    let code = code.to_string();
+                    match Style::from_on_code(&code) {
+                        Some(style) if !styles.contains(&style) => styles.push(style),
+                        Some(_) => {},
+                        None => styles.retain(|s| s.off_code() != code),
+                    }
+                    continue;
+                },
             };
-            hex_u.push(m);
+            if let Some(color) = color {
+                if matches!(code, 40..=49 | 100..=107) {
+                    bg = color;
+                } else {
+                    fg = color;
+                }
+            }
+        }
+        (fg, bg, styles)
+    }
+
+    /// the RGB value of a color, base colors use the standard xterm palette
+    /// @internal
+    pub(crate) fn to_rgb(color: &COLORS) -> Option<(u8, u8, u8)> {
+        match color {
+            COLORS::Black => Some(ANSI16_RGB[0]),
+            COLORS::Red => Some(ANSI16_RGB[1]),
+            COLORS::Green => Some(ANSI16_RGB[2]),
+            COLORS::Yellow => Some(ANSI16_RGB[3]),
+            COLORS::Blue => Some(ANSI16_RGB[4]),
+            COLORS::Magenta => Some(ANSI16_RGB[5]),
+            COLORS::Cyan => Some(ANSI16_RGB[6]),
+            COLORS::White => Some(ANSI16_RGB[7]),
+            #[cfg(feature = "truecolor")]
+            COLORS::HEX(hex) => Some(Color::split_rgb(Color::string_to_hexdec(hex))),
+            #[cfg(feature = "truecolor")]
+            COLORS::Rgb(r, g, b) => Some((*r, *g, *b)),
+            COLORS::Ansi256(n) => Some(Color::ansi256_to_rgb(*n)),
+            COLORS::Default | COLORS::None | COLORS::Transparent => None,
+        }
+    }
+
+    /// the index of the nearest color in the 256-color palette
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// assert_eq!(196, Color::rgb_to_ansi256(255, 0, 0));
+    /// assert_eq!(244, Color::rgb_to_ansi256(128, 128, 128));
+    /// ```
+    pub fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+        let level = |c: u8| match c {
+            0..=47 => 0,
+            48..=114 => 1,
+            _ => (c - 35) / 40,
+        };
+        let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+        // the gray ramp goes from 8 to 238 in steps of 10
+        let average = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+        let gray = 232 + ((average.max(8) - 8 + 5) / 10).min(23);
+        let distance = |n: u8| Color::redmean_squared((r, g, b), Color::ansi256_to_rgb(n));
+        if distance(gray) < distance(cube) {
+            gray
+        } else {
+            cube
+        }
+    }
+
+    /// the index of a color in the 256-color palette, `None` for `Default` and `None`
+    /// the base colors are the first eight entries of the palette, so they keep their index
+    /// instead of being matched by their rgb value
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// assert_eq!(Some(1), Color::to_ansi256(&COLORS::Red));
+    /// assert_eq!(Some(208), Color::to_ansi256(&COLORS::Ansi256(208)));
+    /// ```
+    pub fn to_ansi256(color: &COLORS) -> Option<u8> {
+        match color {
+            COLORS::Black => Some(0),
+            COLORS::Red => Some(1),
+            COLORS::Green => Some(2),
+            COLORS::Yellow => Some(3),
+            COLORS::Blue => Some(4),
+            COLORS::Magenta => Some(5),
+            COLORS::Cyan => Some(6),
+            COLORS::White => Some(7),
+            COLORS::Ansi256(n) => Some(*n),
+            COLORS::Default | COLORS::None | COLORS::Transparent => None,
+            #[cfg(feature = "truecolor")]
+            _ => Color::to_rgb(color).map(|(r, g, b)| Color::rgb_to_ansi256(r, g, b)),
+        }
+    }
+
+    /// the index of the nearest color in the 88-color palette used by rxvt
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// assert_eq!(64, Color::rgb_to_ansi88(255, 0, 0));
+    /// ```
+    pub fn rgb_to_ansi88(r: u8, g: u8, b: u8) -> u8 {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        let nearest = |levels: &[u8], c: u8| {
+            (0..levels.len()).min_by_key(|&i| d(levels[i], c)).unwrap()
+        };
+        let (ri, gi, bi) = (
+            nearest(&ANSI88_CUBE_LEVELS, r),
+            nearest(&ANSI88_CUBE_LEVELS, g),
+            nearest(&ANSI88_CUBE_LEVELS, b),
+        );
+        let cube = (ANSI88_CUBE_LEVELS[ri], ANSI88_CUBE_LEVELS[gi], ANSI88_CUBE_LEVELS[bi]);
+        let gray = nearest(&ANSI88_GRAYS, ((r as u16 + g as u16 + b as u16) / 3) as u8);
+        let level = ANSI88_GRAYS[gray];
+        if Color::redmean_squared((r, g, b), (level, level, level)) < Color::redmean_squared((r, g, b), cube) {
+            80 + gray as u8
+        } else {
+            16 + 16 * ri as u8 + 4 * gi as u8 + bi as u8
+        }
+    }
+
+    /// the rgb value of an entry of the 256-color palette
+    pub fn ansi256_to_rgb(n: u8) -> (u8, u8, u8) {
+        match n {
+            0..=15 => ANSI16_RGB[n as usize],
+            16..=231 => {
+                let n = n - 16;
+                (
+                    CUBE_LEVELS[(n / 36) as usize],
+                    CUBE_LEVELS[(n / 6 % 6) as usize],
+                    CUBE_LEVELS[(n % 6) as usize],
+                )
+            },
+            _ => {
+                let level = 8 + (n - 232) * 10;
+                (level, level, level)
+            },
+        }
+    }
+
+    /// the nearest of the eight base colors
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// assert_eq!(COLORS::Red, Color::rgb_to_basic(230, 20, 10));
+    /// ```
+    pub fn rgb_to_basic(r: u8, g: u8, b: u8) -> COLORS {
+        let distance = |&rgb: &RgbValue| Color::redmean_squared((r, g, b), rgb);
+        let (index, _) = ANSI16_RGB[..8]
+            .iter()
+            .enumerate()
+            .min_by(|(_, x), (_, y)| distance(x).total_cmp(&distance(y)))
+            .unwrap();
+        Color::base_color(index)
+    }
+
+    /// the base color with the given index, 0 is black and 7 is white
+    fn base_color(index: usize) -> COLORS {
+        [
+            COLORS::Black,
+            COLORS::Red,
+            COLORS::Green,
+            COLORS::Yellow,
+            COLORS::Blue,
+            COLORS::Magenta,
+            COLORS::Cyan,
+            COLORS::White,
+        ][index]
+            .clone()
+    }
+
+    /// parses a css hex color like `#fff` or `#ab1346`, case and surrounding whitespace are ignored
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// assert_eq!(Ok(COLORS::Rgb(255, 255, 255)), Color::parse_hex(" #fff"));
+    /// assert_eq!(Err(HexError::Empty), Color::parse_hex("#"));
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn parse_hex(hex: &str) -> Result<COLORS, HexError> {
+        Ok(Color::from_rgb_u32(Color::try_string_to_hexdec(hex)?))
+    }
+
+    /// a color from a packed `0xRRGGBB` value, the highest byte is ignored
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::color::*;
+    /// assert_eq!(COLORS::Rgb(255, 128, 0), Color::from_rgb_u32(0xFF8000));
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn from_rgb_u32(value: u32) -> COLORS {
+        let (r, g, b) = Color::split_rgb(value);
+        COLORS::Rgb(r, g, b)
+    }
+
+    #[cfg(feature = "truecolor")]
+    fn string_to_hexdec(hex: &str) -> u32 {
+        match Color::try_string_to_hexdec(hex) {
+            Ok(c) => c,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[cfg(feature = "truecolor")]
+    fn try_string_to_hexdec(hex: &str) -> Result<u32, HexError> {
+        // surrounding whitespace and a single leading '#' are allowed, everything else must be a hex digit
+        let hex = hex.trim();
+        let hex = hex.strip_prefix('#').unwrap_or(hex).trim_start();
+        if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(HexError::InvalidDigit(c));
+        }
+        let digits: Vec<char> = hex.chars().collect();
+        let digits: Vec<char> = match digits.len() {
+            0 => return Err(HexError::Empty),
+            3 => digits.iter().flat_map(|&c| [c, c]).collect(),
+            6 => digits,
+            len => return Err(HexError::InvalidLength(len)),
         };
         let mut result = 0;
-        let x: u32 = 16;
-        for (index, &u) in hex_u.iter().enumerate() {
-            result += u * x.pow(index as u32);
+        for c in digits {
+            // only ascii hex digits are left, so the conversion cannot fail
+            result = result * 16 + c.to_digit(16).unwrap_or(0);
         }
-        return result;
+        Ok(result)
     }
 
+    #[cfg(feature = "truecolor")]
     fn convert_hex_to_ansi(color: u32) -> String {
-        let c = Box::new(color);
-        let r: u32 = ((*c >> 16) & 255) as u32;
-        let g: u32 = ((*c >> 8) & 255) as u32;
-        let b: u32 = (*c & 255) as u32;
-        String::from(format!("8;2;{};{};{}", r, g, b))
+        let (r, g, b) = Color::split_rgb(color);
+        format!("8;2;{};{};{}", r, g, b)
+    }
+
+    #[cfg(feature = "truecolor")]
+    fn split_rgb(color: u32) -> RgbValue {
+        ((color >> 16) as u8, (color >> 8) as u8, color as u8)
     }
 }
\ No newline at end of file
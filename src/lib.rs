@@ -9,6 +9,7 @@ Example
 ```
 use terminal_color_builder::OutputFormatter as tcb;
 
+# #[cfg(feature = "truecolor")] {
 println!(
     "{}",
     tcb::new()
@@ -19,6 +20,7 @@ println!(
     .text_str("A text in white with a red background.") // print text
     .print() // render to string
 );
+# }
 ```
 
 This is chainable for as long as necessary. Building rainbox-esque outputs through this is absolutely possible.
@@ -28,6 +30,7 @@ Example
 use terminal_color_builder::OutputFormatter as tcb;
 
 /// Building a rainbow-colored text
+# #[cfg(feature = "truecolor")] {
 println!(
     "{}",
     tcb::new()
@@ -40,16 +43,91 @@ println!(
     .fg().red().text_str("W")
     .print() // render to string
 );
+# }
 ```
 
 */
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod macros;
+
 pub mod color;
+#[cfg(feature = "log")]
+pub mod logging;
+pub mod mode;
+#[cfg(feature = "truecolor")]
+pub mod palette;
+mod parser;
+pub mod render;
+pub mod style;
+#[cfg(feature = "termcolor")]
+pub mod write_color;
+#[cfg(all(windows, feature = "winconsole"))]
+pub mod winconsole;
+
+/// Re-exports the types needed for building colored output
+///
+/// Example
+/// ```
+/// use terminal_color_builder::prelude::*;
+/// let c = Color::new(COLORS::Green, COLORS::White);
+/// let s = OutputFormatter::new().fg().green().text_str("Hi").print();
+/// ```
+pub mod prelude {
+    pub use crate::color::{Color, COLORS};
+    pub use crate::mode::{ColorMode, ColorSupport};
+    pub use crate::render::{HtmlRenderer, MarkdownRenderer, Renderer};
+    pub use crate::style::{Style, StyleFlags};
+    pub use crate::{colored, LineEnding, OutputFormatter, RenderReset};
+}
 
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
 use color::*;
+use core::fmt;
+use mode::ColorMode;
+#[cfg(feature = "truecolor")]
+use mode::ColorSupport;
+use render::{RenderedSegment, Renderer};
+use style::{Style, StyleFlags};
+#[cfg(feature = "unicode-width")]
+use unicode_width::UnicodeWidthStr;
 
+#[derive(Debug, Clone)]
 pub struct OutputFormatter {
     output: Vec<Vec<String>>,
+    line_prefix: Option<String>,
+    reset_policy: RenderReset,
+    line_ending: LineEnding,
+    indent: usize,
+    saved_styles: Vec<Vec<String>>,
+}
+
+/// What the builder emits to reset colors and styles, see `OutputFormatter::reset_policy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderReset {
+    /// reset everything with `0` whenever colors change and at the end, robust against state
+    /// leaked by other output
+    Full,
+    /// reset only what was changed, e.g. `39;49` for colors
+    #[default]
+    Minimal,
+}
+
+/// The line terminator newlines in the text are rendered as, see `OutputFormatter::line_ending`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`, as used by unix terminals
+    #[default]
+    Lf,
+    /// `\r\n`, e.g. for files read on windows
+    Crlf,
 }
 
 enum StyleType {
@@ -63,18 +141,83 @@ pub struct OutputColor {
     for_style: StyleType,
 }
 
+/// render a single text in the given colors, without building a chain
+///
+/// Example
+/// ```
+/// use terminal_color_builder::*;
+/// use terminal_color_builder::color::COLORS;
+/// let s = colored(COLORS::Red, COLORS::None, "failed");
+/// assert_eq!("\u{1b}[31mfailed\u{1b}[39;49m", s);
+/// ```
+pub fn colored(fg: COLORS, bg: COLORS, text: &str) -> String {
+    OutputFormatter::new().custom(fg, bg).text_str(text).print()
+}
+
+/// remove all ANSI escape sequences (colors, styles, links, ...) from a string
+///
+/// Example
+/// ```
+/// use terminal_color_builder::*;
+/// assert_eq!("failed", strip_ansi("\u{1b}[31mfailed\u{1b}[39;49m"));
+/// ```
+pub fn strip_ansi(s: &str) -> String {
+    parser::strip(s)
+}
+
+impl Default for OutputFormatter {
+    fn default() -> Self {
+        OutputFormatter::new()
+    }
+}
+
 /// This struct is used to create the builder for CLI-colors
 /// the following example creates a string "hi", that has white (as hex-color) foreground and green background
 ///
 /// Example
 /// ```
 /// use terminal_color_builder::*;
+/// # #[cfg(feature = "truecolor")]
 /// let str = OutputFormatter::new().fg().hex("#fff").bg().green().text("Hi".to_string()).print();
 /// ```
 impl OutputFormatter {
     pub fn new() -> Self {
         OutputFormatter {
             output: vec![],
+            line_prefix: None,
+            reset_policy: RenderReset::Minimal,
+            line_ending: LineEnding::Lf,
+            indent: 0,
+            saved_styles: vec![],
+        }
+    }
+
+    /// create a builder from a string that already contains ANSI escape sequences
+    /// SGR sequences become colors and styles, every other escape sequence is kept as is
+    /// bright colors (`90`-`97`, `100`-`107`) become their entry of the 256-color palette, so
+    /// they can be transformed like every other color
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::from_ansi("\x1b[31mred\x1b[39;49m").invert();
+    /// assert_eq!("\x1b[39;41mred\x1b[39;49m", c.print());
+    /// ```
+    pub fn from_ansi(s: &str) -> Self {
+        let mut output = parser::parse(s);
+        for v in output.iter_mut() {
+            if let Entry::Codes(_) = Entry::of(v) {
+                for code in v.iter_mut() {
+                    if let Ok(n @ (90..=97 | 100..=107)) = code.parse::<u16>() {
+                        let (fg, bg, _) = Color::parse_sgr(&[n]);
+                        *code = Color::new(fg, bg).apply().concat();
+                    }
+                }
+            }
+        }
+        OutputFormatter {
+            output,
+            ..OutputFormatter::new()
         }
     }
 
@@ -112,10 +255,132 @@ impl OutputFormatter {
         custom.custom(fg, bg)
     }
 
+    /// set the foreground by hex value, short for `.fg().hex(color)`
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg_hex("#fff").bg_hex("#000").text_str("x");
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn fg_hex(self, color: &str) -> OutputFormatter {
+        self.fg().hex(color)
+    }
+
+    /// set the background by hex value, short for `.bg().hex(color)`
+    #[cfg(feature = "truecolor")]
+    pub fn bg_hex(self, color: &str) -> OutputFormatter {
+        self.bg().hex(color)
+    }
+
+    /// apply foreground and background by hex value in one call
+    /// panics when one of the hex values is invalid, see `Color::parse_hex` for the error
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().custom_hex("#fff", "#000").text_str("x");
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn custom_hex(self, fg: &str, bg: &str) -> OutputFormatter {
+        let parse = |hex: &str| Color::parse_hex(hex).unwrap_or_else(|e| panic!("{}", e));
+        self.custom(parse(fg), parse(bg))
+    }
+
+    /// apply a `Color` built up front, e.g. by blending or lightening, with its colors and styles
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::{Color, COLORS};
+    /// let warning = Color::new(COLORS::Yellow, COLORS::None);
+    /// let c = OutputFormatter::new().apply_color(&warning).text_str("careful");
+    /// assert_eq!("\u{1b}[33mcareful\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn apply_color(mut self, color: &Color) -> OutputFormatter {
+        self.output.push(color.apply());
+        self
+    }
+
+    /// set the foreground to an entry of the 256-color palette
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg_ansi256(208).bg_ansi256(236).text_str("x");
+    /// assert_eq!("\u{1b}[38;5;208;48;5;236mx\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn fg_ansi256(self, n: u8) -> OutputFormatter {
+        self.fg().ansi256(n)
+    }
+
+    /// set the background to an entry of the 256-color palette
+    pub fn bg_ansi256(self, n: u8) -> OutputFormatter {
+        self.bg().ansi256(n)
+    }
+
+    /// set the foreground to the rgb color, or to the nearest color the terminal can show
+    /// without any color support the foreground is left as it is
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::mode::ColorSupport;
+    /// # #[cfg(feature = "std")] {
+    /// let c = OutputFormatter::new().fg_rgb_if_supported(255, 100, 0, &ColorSupport::detect()).text_str("x");
+    /// # }
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn fg_rgb_if_supported(self, r: u8, g: u8, b: u8, support: &ColorSupport) -> OutputFormatter {
+        let color = match support.mode() {
+            ColorMode::TrueColor => COLORS::Rgb(r, g, b),
+            ColorMode::Ansi256 => COLORS::Ansi256(Color::rgb_to_ansi256(r, g, b)),
+            ColorMode::Ansi88 => COLORS::Ansi256(Color::rgb_to_ansi88(r, g, b)),
+            ColorMode::Basic => Color::rgb_to_basic(r, g, b),
+            _ => return self,
+        };
+        self.fg().color(color)
+    }
+
+    /// set the foreground to a color picked from the key, the same key always gets the same
+    /// color, e.g. to tell usernames or thread ids apart. The color is taken from the 6x6x6
+    /// color cube of the 256-color palette, leaving out the darkest entries
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg_from_hash("alice").text_str("alice");
+    /// assert_eq!(c.print(), OutputFormatter::new().fg_from_hash("alice").text_str("alice").print());
+    /// ```
+    pub fn fg_from_hash(self, key: &str) -> OutputFormatter {
+        // cube entries with every channel at level 1 or above, see `Color::ansi256_to_rgb`
+        let n = hash_key(key) % 125;
+        let index = 16 + 36 * (1 + n / 25) + 6 * (1 + n / 5 % 5) + (1 + n % 5);
+        self.fg_ansi256(index as u8)
+    }
+
+    /// set the foreground to a color picked from the key out of the given palette, see
+    /// `fg_from_hash`. An empty palette leaves the foreground as it is
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// let palette = [COLORS::Red, COLORS::Green, COLORS::Blue];
+    /// let c = OutputFormatter::new().fg_from_hash_in("worker-1", &palette).text_str("worker-1");
+    /// ```
+    pub fn fg_from_hash_in(self, key: &str, palette: &[COLORS]) -> OutputFormatter {
+        if palette.is_empty() {
+            return self;
+        }
+        let color = palette[hash_key(key) as usize % palette.len()].clone();
+        self.custom(color, COLORS::None)
+    }
+
     /// add text to apply color for
     pub fn text(mut self, message: String) -> Self {
-        self.output.push(vec!["#text#".to_string(), message]);
-        return self;
+        self.push_text(message);
+        self
     }
 
     /// add text as str to apply color for
@@ -123,154 +388,2158 @@ impl OutputFormatter {
         self.text(message.to_string())
     }
 
-    /// render the builder into a string
-    pub fn print(&self) -> String {
-        let mut message: Vec<String> = vec![];
-        let mut colors: Vec<String> = vec![];
-        let default = &String::from("");
-        let mut use_formatter: bool = false;
-        let mut text = default.clone();
-        for v in &self.output {
-            if v.first().unwrap_or(default) == "#text#" {
-                use_formatter = true;
-                text = v.get(1).unwrap_or(default).clone();
-            }
-            if use_formatter {
-                message.push(Color::format(&colors));
-                message.push(text.clone());
-                text = default.clone();
-                colors = vec![];
-                use_formatter = false;
-            } else {
-                for c in v {
-                    colors.push(c.clone());
-                }
-            }
-        }
-        let clr = Color::new(COLORS::None, COLORS::None);
-        message.push(Color::format(&clr.unapply()));
-        message.join("")
+    /// add a single character as text, e.g. for spinners or per-character colors
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().red().text_char('>').text_str(" next");
+    /// assert_eq!("> next", c.plain());
+    /// ```
+    pub fn text_char(mut self, c: char) -> Self {
+        let mut message = String::with_capacity(c.len_utf8());
+        message.push(c);
+        self.push_text(message);
+        self
     }
-}
 
-/// OutputColor cannot be created on its own. Usage through OutputFormatter
-impl OutputColor {
-    /// Apply black to current context
-    pub fn black(&mut self) -> OutputFormatter {
-        self.colorize(COLORS::Black, COLORS::None)
+    /// add a text with its colors and styles in one call, for building the output in a loop
+    /// this results in the same output as the equivalent chain, e.g. `.fg().red().bold().text_str(text)`
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// use terminal_color_builder::style::Style;
+    /// let mut c = OutputFormatter::new();
+    /// for (name, ok) in [("build", true), ("test", false)] {
+    ///     let color = if ok { COLORS::Green } else { COLORS::Red };
+    ///     c.push_segment(color, COLORS::None, &[Style::Bold], name);
+    /// }
+    /// ```
+    pub fn push_segment(&mut self, fg: COLORS, bg: COLORS, styles: &[Style], text: &str) {
+        let colors = Color::new(fg, bg).apply();
+        if !colors.is_empty() {
+            self.output.push(colors);
+        }
+        for style in styles {
+            self.output.push(vec![style.on_code()]);
+        }
+        self.push_text(text.to_string());
     }
 
-    /// Apply red to current context
-    pub fn red(&mut self) -> OutputFormatter {
-        self.colorize(COLORS::Red, COLORS::None)
+    /// add any value implementing `Display` as text, e.g. numbers
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().green().text_display(3).text_str(" items");
+    /// ```
+    pub fn text_display(self, value: impl fmt::Display) -> Self {
+        self.text(value.to_string())
     }
 
-    /// Apply green to current context
-    pub fn green(&mut self) -> OutputFormatter {
-        self.colorize(COLORS::Green, COLORS::None)
+    /// add a multi-line text, every line gets the current colors and is reset before its
+    /// newline, so backgrounds do not bleed into the next line
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().bg().red().text_lines("a\nb");
+    /// assert_eq!("\u{1b}[41ma\u{1b}[0m\n\u{1b}[41mb\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn text_lines(mut self, text: &str) -> Self {
+        let codes = self.active_codes().codes();
+        if codes.is_empty() {
+            if !text.is_empty() {
+                self.push_text(text.to_string());
+            }
+            return self;
+        }
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.output.push(vec!["0".to_string()]);
+                self.push_text("\n".to_string());
+                // the colors are set again even after a trailing newline, so text added later
+                // keeps them
+                self.output.push(codes.clone());
+            }
+            if !line.is_empty() {
+                self.push_text(line.to_string());
+            }
+        }
+        self
     }
 
-    /// Apply yellow to current context
-    pub fn yellow(&mut self) -> OutputFormatter {
-        self.colorize(COLORS::Yellow, COLORS::None)
+    /// add text with its own foreground color for every character
+    /// if there are fewer colors than characters the colors repeat from the start,
+    /// an empty text or an empty list of colors adds nothing
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// let c = OutputFormatter::new().char_colors("abc", &[COLORS::Red, COLORS::Green]);
+    /// assert_eq!("\u{1b}[31ma\u{1b}[32mb\u{1b}[31mc\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn char_colors(mut self, text: &str, colors: &[COLORS]) -> Self {
+        for (c, color) in text.chars().zip(colors.iter().cycle()) {
+            self.output.push(Color::new(color.clone(), COLORS::None).apply());
+            self.push_text(c.to_string());
+        }
+        self
     }
 
-    /// Apply blue to current context
-    pub fn blue(&mut self) -> OutputFormatter {
-        self.colorize(COLORS::Blue, COLORS::None)
+    /// transform the content of every text, the colors stay as they are
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().red().text_str(" a ").map_text(|t| t.trim().to_string());
+    /// assert_eq!("a", c.plain());
+    /// ```
+    pub fn map_text<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> String,
+    {
+        for v in self.output.iter_mut() {
+            if let Entry::Text(text) = Entry::of(v) {
+                v[1] = f(text);
+            }
+        }
+        self
     }
 
-    /// Apply magenta to current context
-    pub fn magenta(&mut self) -> OutputFormatter {
-        self.colorize(COLORS::Magenta, COLORS::None)
+    /// replace every character of the text with the mask, e.g. to hide secrets in logs
+    /// the colors and the whitespace stay, so the layout of the output does not change
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().text_str("token: ").fg().red().text_str("s3cr3t").redact('*');
+    /// assert_eq!("****** ******", c.plain());
+    /// ```
+    pub fn redact(self, mask: char) -> Self {
+        self.map_text(|text| text.chars().map(|c| if c.is_whitespace() { c } else { mask }).collect())
     }
 
-    /// Apply cyan to current context
-    pub fn cyan(&mut self) -> OutputFormatter {
-        self.colorize(COLORS::Cyan, COLORS::None)
+    /// shorten the text to at most `max_chars` characters by replacing its middle with the
+    /// ellipsis, the start and the end keep their colors. If the ellipsis alone is longer than
+    /// `max_chars` only as much of the ellipsis as fits is left
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().blue().text_str("very_long_file").fg().green().text_str("_name.rs");
+    /// assert_eq!("very_lo…ame.rs", c.truncate_middle(14, "…").plain());
+    /// ```
+    pub fn truncate_middle(mut self, max_chars: usize, ellipsis: &str) -> Self {
+        let total = self.visible_len();
+        if total <= max_chars {
+            return self;
+        }
+        let ellipsis_len = ellipsis.chars().count();
+        let ellipsis: String = ellipsis.chars().take(max_chars).collect();
+        let keep = max_chars.saturating_sub(ellipsis_len);
+        // the start gets the extra character if the kept characters cannot be split evenly
+        let (head, tail) = (keep - keep / 2, keep / 2);
+        let mut position = 0;
+        let mut inserted = false;
+        for v in self.output.iter_mut() {
+            let text = match Entry::of(v) {
+                Entry::Text(text) => text,
+                _ => continue,
+            };
+            let mut kept = String::new();
+            for c in text.chars() {
+                if position >= head && !inserted {
+                    // the ellipsis takes the colors of the first character it replaces
+                    kept.push_str(&ellipsis);
+                    inserted = true;
+                }
+                if position < head || position >= total - tail {
+                    kept.push(c);
+                }
+                position += 1;
+            }
+            v[1] = kept;
+        }
+        self
     }
 
-    /// Apply white to current context
-    pub fn white(&mut self) -> OutputFormatter {
-        self.colorize(COLORS::White, COLORS::None)
+    /// repeat everything built so far n times, e.g. for a pattern of colored fragments
+    /// repeating it 0 times leaves an empty builder
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().red().text_str("-").fg().blue().text_str("=").repeat_builder(2);
+    /// assert_eq!("-=-=", c.plain());
+    /// ```
+    pub fn repeat_builder(mut self, n: usize) -> Self {
+        let fragment = core::mem::take(&mut self.output);
+        for _ in 0..n {
+            self.output.extend(fragment.iter().cloned());
+        }
+        self
     }
 
-    /// Apply custom color by hex value to current context
+    /// add text without any color or style, no matter what was set before, e.g. plain text
+    /// between colored fields. Unlike `text_str` it does not inherit the colors, the colors
+    /// active before are set again for the following text
+    ///
     /// Example
     /// ```
     /// use terminal_color_builder::*;
-    /// let white = OutputFormatter::new().fg().hex("#fff");
-    /// let rnd = OutputFormatter::new().fg().hex("#ab1346");
+    /// let mut c = OutputFormatter::new().fg().red().text_str("error");
+    /// c.concat_str(": ");
+    /// let c = c.text_str("failed");
+    /// assert_eq!("\u{1b}[31merror\u{1b}[0m: \u{1b}[31mfailed\u{1b}[39;49m", c.print());
     /// ```
-    pub fn hex(&mut self, color: &str) -> OutputFormatter {
-        self.colorize(COLORS::HEX(color.to_string()), COLORS::None)
+    pub fn concat_str(&mut self, s: &str) {
+        self.push_uncolored(s.to_string());
     }
 
-    /// Apply a custom foreground and background
-    pub fn custom(&mut self, fg: COLORS, bg: COLORS) -> OutputFormatter {
-        self.colorize(fg, bg)
+    /// add n spaces without any color or style, e.g. to separate colored fields
+    /// the colors before the spaces are active again for the following text
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().bg().red().text_str("a").spaces(2).text_str("b");
+    /// assert_eq!("\u{1b}[41ma\u{1b}[0m  \u{1b}[41mb\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn spaces(self, n: usize) -> Self {
+        self.uncolored(" ".repeat(n))
     }
 
-    fn colorize(&mut self, fg: COLORS, bg: COLORS) -> OutputFormatter {
-        let color: Vec<String> = match &self.for_style {
-            StyleType::FG => Color::new(fg, bg).apply(),
-            StyleType::BG => Color::new(bg, fg).apply(),
-            StyleType::Both => Color::new(fg, bg).apply(),
-        };
-        self.formatter.output.push(color);
-        return OutputFormatter {
-            output: self.formatter.output.clone()
-        };
+    /// add a tab without any color or style, see `spaces`
+    pub fn tab(self) -> Self {
+        self.uncolored("\t".to_string())
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    /// the foreground color the next text would be printed in, `None` if no foreground is set
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// let c = OutputFormatter::new().fg().green();
+    /// assert_eq!(Some(COLORS::Green), c.current_fg());
+    /// ```
+    pub fn current_fg(&self) -> Option<COLORS> {
+        Color::color_from_code(&self.active_codes().fg)
+    }
 
-    #[test]
-    pub fn test_create_color() {
-        let c = Color::new(COLORS::Green, COLORS::White);
-        assert_eq!("\u{1b}[32;47mhello\u{1b}[39;49m", c.print("hello".to_string()));
+    /// the background color the next text would be printed in, `None` if no background is set
+    pub fn current_bg(&self) -> Option<COLORS> {
+        Color::color_from_code(&self.active_codes().bg)
     }
 
-    #[test]
-    pub fn test_create_only_bg_color() {
-        let c = Color::new(COLORS::None, COLORS::White);
-        assert_eq!("\u{1b}[47mhello\u{1b}[39;49m", c.print("hello".to_string()));
+    /// print the prefix at the start of every line of the text, in the color of the text it is in
+    /// a newline at the very end does not get a prefix
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().line_prefix("> ").text_str("a\nb\n");
+    /// assert_eq!("> a\n> b\n", c.plain());
+    /// ```
+    pub fn line_prefix(mut self, prefix: &str) -> Self {
+        self.line_prefix = Some(prefix.to_string());
+        self
     }
 
-    #[test]
-    pub fn test_create_only_fg_color() {
-        let c = Color::new(COLORS::Black, COLORS::None);
-        assert_eq!("\u{1b}[30mhello\u{1b}[39;49m", c.print("hello".to_string()));
+    /// add text with a foreground stepping evenly through the 256-color palette,
+    /// from index `start` at the first character to index `end` at the last one
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().gradient_256("abc", 196, 200);
+    /// assert_eq!("\u{1b}[38;5;196ma\u{1b}[38;5;198mb\u{1b}[38;5;200mc\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn gradient_256(mut self, text: &str, start: u8, end: u8) -> Self {
+        let steps = text.chars().count().saturating_sub(1).max(1) as i32;
+        for (i, c) in text.chars().enumerate() {
+            let n = start as i32 + (end as i32 - start as i32) * i as i32 / steps;
+            self.output.push(Color::new(COLORS::Ansi256(n as u8), COLORS::None).apply());
+            self.push_text(c.to_string());
+        }
+        self
     }
 
-    #[test]
-    pub fn test_color_builder_green_bg_white_fg() {
-        let c = OutputFormatter::new().fg().white().bg().green().text("Hi".to_string());
-        assert_eq!("\u{1b}[37;42mHi\u{1b}[39;49m", c.print());
+    /// add text with every occurrence of `needle` in the `hit` foreground and the rest of the
+    /// text in the `rest` foreground, e.g. for search results. Occurrences do not overlap and
+    /// an empty needle highlights nothing
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// let c = OutputFormatter::new().highlight("Hello", "h", COLORS::Red, COLORS::Default, false);
+    /// assert_eq!("\u{1b}[31mH\u{1b}[39mello\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn highlight(mut self, text: &str, needle: &str, hit: COLORS, rest: COLORS, case_sensitive: bool) -> Self {
+        let same = |a: char, b: char| if case_sensitive { a == b } else { a.to_lowercase().eq(b.to_lowercase()) };
+        // the length in bytes of the needle if it is found at the start of the text
+        let match_at = |text: &str| {
+            let mut len = 0;
+            let mut chars = text.chars();
+            for n in needle.chars() {
+                match chars.next() {
+                    Some(c) if same(c, n) => len += c.len_utf8(),
+                    _ => return None,
+                }
+            }
+            Some(len)
+        };
+        let (mut start, mut i) = (0, 0);
+        while !needle.is_empty() && i < text.len() {
+            match match_at(&text[i..]) {
+                Some(len) => {
+                    self.push_fg_text(&rest, &text[start..i]);
+                    self.push_fg_text(&hit, &text[i..i + len]);
+                    i += len;
+                    start = i;
+                },
+                None => i += text[i..].chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        self.push_fg_text(&rest, &text[start..]);
+        self
     }
 
-    #[test]
-    pub fn test_color_builder_green_bg_white_fg_custom() {
-        let c = OutputFormatter::new().custom(COLORS::White, COLORS::Green).text("Hi".to_string());
+    /// add text with every number in it in the `color` foreground and the rest of the text in
+    /// the `rest` foreground, e.g. to make durations and counts in logs stand out. With
+    /// `decimals` a leading `-` and a decimal point between digits belong to the number
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// let c = OutputFormatter::new().colorize_numbers("3 files", COLORS::Cyan, COLORS::Default, false);
+    /// assert_eq!("\u{1b}[36m3\u{1b}[39m files\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn colorize_numbers(mut self, text: &str, color: COLORS, rest: COLORS, decimals: bool) -> Self {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let digit_at = |i: usize| chars.get(i).is_some_and(|(_, c)| c.is_ascii_digit());
+        let (mut start, mut i) = (0, 0);
+        while i < chars.len() {
+            let (offset, c) = chars[i];
+            // a minus only starts a number if it is not part of a word, like in `x-1`
+            let after_word = i > 0 && chars[i - 1].1.is_alphanumeric();
+            let signed = decimals && c == '-' && digit_at(i + 1) && !after_word;
+            if !c.is_ascii_digit() && !signed {
+                i += 1;
+                continue;
+            }
+            let mut end = i + 1;
+            let mut has_point = false;
+            while end < chars.len() {
+                if digit_at(end) {
+                    end += 1;
+                } else if decimals && !has_point && chars[end].1 == '.' && digit_at(end + 1) {
+                    has_point = true;
+                    end += 1;
+                } else {
+                    break;
+                }
+            }
+            let end_offset = chars.get(end).map_or(text.len(), |(o, _)| *o);
+            self.push_fg_text(&rest, &text[start..offset]);
+            self.push_fg_text(&color, &text[offset..end_offset]);
+            start = end_offset;
+            i = end;
+        }
+        self.push_fg_text(&rest, &text[start..]);
+        self
+    }
+
+    /// adds a text in its own foreground color, an empty text adds nothing
+    fn push_fg_text(&mut self, color: &COLORS, text: &str) {
+        if !text.is_empty() {
+            self.output.push(Color::new(color.clone(), COLORS::None).apply());
+            self.push_text(text.to_string());
+        }
+    }
+
+    /// indent every line by n spaces when rendering, the spaces are never colored so backgrounds
+    /// start after the indent
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().indent(2).bg().red().text_str("a\nb");
+    /// assert_eq!("  a\n  b", c.plain());
+    /// assert_eq!("  \u{1b}[41ma\n\u{1b}[0m  \u{1b}[41mb\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn indent(mut self, n: usize) -> Self {
+        self.indent = n;
+        self
+    }
+
+    /// choose the line terminator newlines in the text are rendered as, `LineEnding::Lf` by default
+    /// this also applies to the newlines inserted by e.g. `text_lines` or `wrap`
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().line_ending(LineEnding::Crlf).text_str("a\nb");
+    /// assert_eq!("a\r\nb", c.plain());
+    /// ```
+    pub fn line_ending(mut self, ending: LineEnding) -> Self {
+        self.line_ending = ending;
+        self
+    }
+
+    /// choose how colors and styles are reset, `RenderReset::Minimal` by default
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().reset_policy(RenderReset::Full).fg().red().text_str("a");
+    /// assert_eq!("\u{1b}[0;31ma\u{1b}[0m", c.print());
+    /// ```
+    pub fn reset_policy(mut self, policy: RenderReset) -> Self {
+        self.reset_policy = policy;
+        self
+    }
+
+    /// add a progress bar `width` characters wide, the done part in the `filled` color and the rest
+    /// in the `empty` color. The fraction is clamped to 0 - 1
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// let c = OutputFormatter::new().progress_bar(0.5, 10, COLORS::Green, COLORS::White);
+    /// assert_eq!("█████░░░░░", c.plain());
+    /// ```
+    pub fn progress_bar(self, fraction: f32, width: usize, filled: COLORS, empty: COLORS) -> Self {
+        let done = ((fraction.clamp(0.0, 1.0) * width as f32 + 0.5) as usize).min(width);
+        let mut bar = self;
+        if done > 0 {
+            bar = bar.custom(filled, COLORS::None).text("█".repeat(done));
+        }
+        if done < width {
+            bar = bar.custom(empty, COLORS::None).text("░".repeat(width - done));
+        }
+        bar
+    }
+
+    /// draw a box around the text, the border is printed in the given color and the colors
+    /// inside the box stay as they are. The box is as wide as the widest line
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// let c = OutputFormatter::new().fg().red().text_str("hi").boxed(COLORS::Blue);
+    /// assert_eq!("┌────┐\n│ hi │\n└────┘", c.plain());
+    /// ```
+    pub fn boxed(mut self, border_color: COLORS) -> OutputFormatter {
+        let mut lines: Vec<Vec<Vec<String>>> = vec![vec![]];
+        for v in core::mem::take(&mut self.output) {
+            match Entry::of(&v) {
+                Entry::Text(text) => {
+                    for (i, part) in text.split('\n').enumerate() {
+                        if i > 0 {
+                            lines.push(vec![]);
+                        }
+                        if !part.is_empty() {
+                            lines.last_mut().unwrap().push(vec!["#text#".to_string(), part.to_string()]);
+                        }
+                    }
+                },
+                _ => lines.last_mut().unwrap().push(v),
+            }
+        }
+        // a trailing newline does not start another line inside the box
+        if lines.len() > 1 && !lines.last().unwrap().iter().any(|v| matches!(Entry::of(v), Entry::Text(_))) {
+            let rest = lines.pop().unwrap();
+            lines.last_mut().unwrap().extend(rest);
+        }
+        let widths: Vec<usize> = lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|v| match Entry::of(v) {
+                        Entry::Text(text) => text_width(text),
+                        _ => 0,
+                    })
+                    .sum()
+            })
+            .collect();
+        let width = widths.iter().copied().max().unwrap_or(0);
+        let border = Color::new(border_color, COLORS::None).apply();
+        let horizontal = "─".repeat(width + 2);
+
+        let mut boxed = self;
+        let mut active = ActiveCodes::default();
+        boxed.output.push(border.clone());
+        boxed.push_text(format!("┌{}┐\n", horizontal));
+        for (line, line_width) in lines.iter().zip(widths) {
+            boxed.output.push(border.clone());
+            boxed.push_text("│ ".to_string());
+            boxed.output.push(vec!["0".to_string()]);
+            let codes = active.codes();
+            if !codes.is_empty() {
+                boxed.output.push(codes);
+            }
+            for v in line {
+                if let Entry::Codes(codes) = Entry::of(v) {
+                    active.changed(codes);
+                }
+                boxed.output.push(v.clone());
+            }
+            boxed.output.push(vec!["0".to_string()]);
+            boxed.push_text(" ".repeat(width - line_width));
+            boxed.output.push(border.clone());
+            boxed.push_text(" │\n".to_string());
+        }
+        boxed.output.push(border);
+        boxed.push_text(format!("└{}┘", horizontal));
+        boxed
+    }
+
+    /// wrap the text so no line is wider than `width` columns, lines are broken at spaces and
+    /// words longer than a line are broken inside, a word split over several texts is still one
+    /// word. The colors are reset before every inserted newline and set again after it, like
+    /// `text_lines` does
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().red().text_str("one two three").wrap(8);
+    /// assert_eq!("one two\nthree", c.plain());
+    /// ```
+    pub fn wrap(mut self, width: usize) -> OutputFormatter {
+        if width == 0 {
+            return self;
+        }
+        // the breaks are found in the whole text, so a word spanning several texts is not broken
+        // where one of them ends. Each break removes the bytes `start..end` and puts a newline
+        // there if `newline` is set
+        let full: String = self.texts().collect();
+        let mut breaks: Vec<(usize, usize, bool)> = vec![];
+        let mut offset = 0;
+        for part in full.split('\n') {
+            let mut line_start = offset;
+            let mut column = 0;
+            for (j, word) in part.split(' ').enumerate() {
+                if j > 0 {
+                    let word_width = text_width(word);
+                    if column + 1 + word_width <= width {
+                        column += 1 + word_width;
+                        offset += 1 + word.len();
+                        continue;
+                    }
+                    // the spaces the line is broken at are not printed
+                    let start = full[..offset].trim_end_matches(' ').len().max(line_start);
+                    breaks.push((start, offset + 1, column > 0));
+                    column = 0;
+                    offset += 1;
+                    line_start = offset;
+                }
+                for c in word.chars() {
+                    let char_width = text_width(c.encode_utf8(&mut [0; 4]));
+                    if column > 0 && column + char_width > width {
+                        breaks.push((offset, offset, true));
+                        column = 0;
+                        line_start = offset;
+                    }
+                    column += char_width;
+                    offset += c.len_utf8();
+                }
+            }
+            offset += 1;
+        }
+        let output = core::mem::take(&mut self.output);
+        let mut wrapped = self;
+        let mut active = ActiveCodes::default();
+        let mut next = 0;
+        let mut offset = 0;
+        for v in &output {
+            let text = match Entry::of(v) {
+                Entry::Text(text) => text,
+                entry => {
+                    if let Entry::Codes(codes) = entry {
+                        active.changed(codes);
+                    }
+                    wrapped.output.push(v.clone());
+                    continue;
+                },
+            };
+            let codes = active.codes();
+            let mut line = String::new();
+            for (i, c) in text.char_indices() {
+                let at = offset + i;
+                while let Some((start, end, newline)) = breaks.get_mut(next) {
+                    if *start > at {
+                        break;
+                    }
+                    if *newline {
+                        *newline = false;
+                        if !line.is_empty() {
+                            wrapped.push_text(core::mem::take(&mut line));
+                        }
+                        wrapped.output.push(vec!["0".to_string()]);
+                        wrapped.push_text("\n".to_string());
+                        if !codes.is_empty() {
+                            wrapped.output.push(codes.clone());
+                        }
+                    }
+                    if *end > at {
+                        break;
+                    }
+                    next += 1;
+                }
+                // the character is one of the removed spaces
+                if breaks.get(next).is_some_and(|(start, _, _)| *start <= at) {
+                    continue;
+                }
+                line.push(c);
+            }
+            offset += text.len();
+            if !line.is_empty() {
+                wrapped.push_text(line);
+            }
+        }
+        wrapped
+    }
+
+    /// render the builder into a string
+    pub fn print(&self) -> String {
+        if let Some(message) = self.print_single_segment() {
+            return message;
+        }
+        let mut message = self.render_without_trailing_reset();
+        message.push_str(&self.final_reset());
+        message
+    }
+
+    /// renders the common case of a single text with only codes before it straight into one
+    /// string, the output is the same as the general path of `print`
+    fn print_single_segment(&self) -> Option<String> {
+        let plain_lines = self.line_prefix.is_none() && self.line_ending == LineEnding::Lf && self.indent == 0;
+        if !plain_lines || self.reset_policy != RenderReset::Minimal {
+            return None;
+        }
+        let (last, codes) = self.output.split_last()?;
+        let text = match Entry::of(last) {
+            Entry::Text(text) => text,
+            _ => return None,
+        };
+        if !codes.iter().all(|v| matches!(Entry::of(v), Entry::Codes(_))) {
+            return None;
+        }
+        let mut codes: Vec<&String> = codes.iter().flatten().collect();
+        fg_before_bg(&mut codes);
+        let mut message = String::with_capacity(text.len() + 24);
+        for (i, code) in codes.into_iter().enumerate() {
+            message.push_str(if i == 0 { "\x1b[" } else { ";" });
+            message.push_str(code);
+        }
+        if !message.is_empty() {
+            message.push('m');
+        }
+        message.push_str(text);
+        message.push_str(&self.final_reset());
+        Some(message)
+    }
+
+    /// render the builder like `print`, but without the reset at the end, e.g. for output that is
+    /// embedded in a larger stream managing its own colors. The caller is responsible for
+    /// resetting the colors afterwards, see `final_reset`
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().red().text_str("a");
+    /// assert_eq!("\u{1b}[31ma", c.render_without_trailing_reset());
+    /// ```
+    pub fn render_without_trailing_reset(&self) -> String {
+        self.segments().map(|(escape, text)| escape + &text).collect()
+    }
+
+    /// the rendered pieces of the builder, every text with the escape sequence printed before it
+    /// the reset at the end of the output is not part of the segments, see `final_reset`
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().red().text_str("a").bg().blue().text_str("b");
+    /// for (escape, text) in c.segments() {
+    ///     print!("{}{}", escape, text);
+    /// }
+    /// print!("{}", c.final_reset());
+    /// ```
+    pub fn segments(&self) -> impl Iterator<Item = (String, String)> {
+        self.escaped_segments(false).into_iter()
+    }
+
+    /// the texts of the builder with the colors and styles active for each of them, e.g. to
+    /// check the structure of the output in tests instead of comparing escape sequences
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// let c = OutputFormatter::new().fg().red().text_str("a");
+    /// assert_eq!(Some(COLORS::Red), c.to_segments()[0].fg);
+    /// ```
+    pub fn to_segments(&self) -> Vec<RenderedSegment> {
+        render::decode(self)
+    }
+
+    /// write the rendered builder to a writer, segment by segment
+    /// each segment is written as soon as it is rendered, the whole output is never built first
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let mut out: Vec<u8> = vec![];
+    /// OutputFormatter::new().fg().red().text_str("failed").write_to(&mut out).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        self.for_each_segment(false, |escape, text| {
+            w.write_all(escape.as_bytes())?;
+            w.write_all(text.as_bytes())
+        })?;
+        w.write_all(self.final_reset().as_bytes())
+    }
+
+    /// render the builder into bytes, for writers that work on bytes instead of strings
+    /// the rendered buffer is handed over as is, so this does not copy the output
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.print().into_bytes()
+    }
+
+    /// render the builder like `print`, but leave out color codes that are still active from
+    /// the previous segment. This relies on the terminal keeping its state between segments,
+    /// so it is shorter but less robust than `print`
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new()
+    ///     .fg().white().bg().red().text_str("a")
+    ///     .fg().white().bg().blue().text_str("b");
+    /// assert_eq!("\u{1b}[37;41ma\u{1b}[44mb\u{1b}[39;49m", c.render_minified());
+    /// ```
+    pub fn render_minified(&self) -> String {
+        let mut message: String = self
+            .escaped_segments(true)
+            .into_iter()
+            .map(|(escape, text)| escape + &text)
+            .collect();
+        message.push_str(&self.final_reset());
+        message
+    }
+
+    fn escaped_segments(&self, minified: bool) -> Vec<(String, String)> {
+        let mut segments: Vec<(String, String)> = vec![];
+        let _ = self.for_each_segment(minified, |escape, text| {
+            segments.push((escape.to_string(), text.to_string()));
+            Ok::<(), core::convert::Infallible>(())
+        });
+        segments
+    }
+
+    /// render the segments one after the other, the escape buffer is reused between them
+    fn for_each_segment<E>(&self, minified: bool, mut emit: impl FnMut(&str, &str) -> Result<(), E>) -> Result<(), E> {
+        let mut escape = String::new();
+        let mut colors: Vec<String> = vec![];
+        let mut active: Vec<String> = vec![];
+        let mut active_codes = ActiveCodes::default();
+        let mut link: Option<&str> = None;
+        let mut link_open = false;
+        let mut prefixer = LinePrefixer::new(self.line_prefix.as_deref(), self.line_ending, self.indent);
+        for entry in self.entries() {
+            match entry {
+                Entry::Codes(codes) => colors.extend(codes.iter().cloned()),
+                Entry::Text(text) => {
+                    if link_open {
+                        escape.push_str(&Color::link(""));
+                        link_open = false;
+                    }
+                    // with nothing colored yet the indent goes before the codes, so they do not have
+                    // to be opened and switched off again right away
+                    if !text.is_empty() && active_codes.codes().is_empty() {
+                        if let Some(indent) = prefixer.take_indent() {
+                            escape.push_str(&indent);
+                        }
+                    }
+                    fg_before_bg(&mut colors);
+                    let changed = active_codes.changed(&colors);
+                    if minified {
+                        if !changed.is_empty() {
+                            Color::format_into(&mut escape, &changed);
+                        }
+                    } else if !colors.is_empty() && colors != active {
+                        // text without new colors keeps the ones that are already active,
+                        // repeating the active colors would only add escape overhead
+                        if self.reset_policy == RenderReset::Full {
+                            let mut codes = vec!["0".to_string()];
+                            codes.extend(active_codes.codes());
+                            Color::format_into(&mut escape, &codes);
+                        } else {
+                            Color::format_into(&mut escape, &colors);
+                        }
+                        active = colors.clone();
+                    }
+                    colors.clear();
+                    if let Some(url) = link.take() {
+                        escape.push_str(&Color::link(url));
+                        link_open = true;
+                    }
+                    let text = prefixer.apply(text, &active_codes.codes());
+                    emit(&escape, &text)?;
+                    escape.clear();
+                },
+                Entry::Raw(raw) => escape.push_str(raw),
+                Entry::Link(url) => link = Some(url),
+                Entry::Transparent(_) => {},
+            }
+        }
+        if link_open {
+            escape.push_str(&Color::link(""));
+        }
+        // codes after the last text are still printed, unless they only switch off what the
+        // final reset switches off anyway
+        fg_before_bg(&mut colors);
+        let pending = if minified { active_codes.changed(&colors) } else { colors };
+        if pending.iter().any(|code| !is_off_code(code)) {
+            Color::format_into(&mut escape, &pending);
+        }
+        if !escape.is_empty() {
+            emit(&escape, "")?;
+        }
+        Ok(())
+    }
+
+    /// render the builder with as many colors as the given mode allows
+    /// colors the mode cannot show are mapped to the nearest color it can
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::mode::ColorMode;
+    /// # #[cfg(feature = "truecolor")] {
+    /// let c = OutputFormatter::new().fg().rgb(255, 0, 0).text_str("hot");
+    /// assert_eq!("\u{1b}[38;5;196mhot\u{1b}[39;49m", c.render(ColorMode::Ansi256));
+    /// assert_eq!("\u{1b}[31mhot\u{1b}[39;49m", c.render(ColorMode::Basic));
+    /// # }
+    /// ```
+    pub fn render(&self, mode: ColorMode) -> String {
+        match mode.resolve() {
+            ColorMode::TrueColor | ColorMode::Auto => self.print(),
+            ColorMode::None => self.plain(),
+            ColorMode::NoneKeepLinks => {
+                let mut links = self.clone();
+                links.output.retain(|v| matches!(Entry::of(v), Entry::Text(_) | Entry::Link(_)));
+                links.segments().map(|(escape, text)| escape + &text).collect()
+            },
+            mode => {
+                let mut converted = self.clone();
+                for v in converted.output.iter_mut() {
+                    if let Entry::Codes(_) = Entry::of(v) {
+                        for code in v.iter_mut() {
+                            *code = mode.convert(code);
+                        }
+                    }
+                }
+                converted.print()
+            },
+        }
+    }
+
+    /// render only the text, without any color codes
+    pub fn plain(&self) -> String {
+        let mut prefixer = LinePrefixer::new(self.line_prefix.as_deref(), self.line_ending, self.indent);
+        self.texts().map(|t| prefixer.apply(t, &[])).collect()
+    }
+
+    /// number of characters in the text, color codes are not counted
+    pub fn visible_len(&self) -> usize {
+        self.texts().map(|t| t.chars().count()).sum()
+    }
+
+    /// number of terminal columns the text takes up, wide characters (e.g. CJK) count as two
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().red().text_str("日本");
+    /// assert_eq!(2, c.visible_len());
+    /// assert_eq!(4, c.display_width());
+    /// ```
+    #[cfg(feature = "unicode-width")]
+    pub fn display_width(&self) -> usize {
+        self.texts().map(UnicodeWidthStr::width).sum()
+    }
+
+    /// Turn the following text into a hyperlink
+    /// terminals supporting OSC 8 make it clickable, the other renderers use their own link syntax
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().blue().link("https://docs.rs").text_str("docs");
+    /// ```
+    pub fn link(mut self, url: &str) -> Self {
+        self.output.push(vec!["#link#".to_string(), url.to_string()]);
+        self
+    }
+
+    /// render the builder with a custom renderer, e.g. `MarkdownRenderer` or `HtmlRenderer`
+    pub fn render_with<R: Renderer>(&self, renderer: &R) -> String {
+        renderer.render(self)
+    }
+
+    /// Reset all colors and styles for the following text
+    pub fn reset(mut self) -> Self {
+        self.output.push(vec!["0".to_string()]);
+        self
+    }
+
+    /// remove everything built so far, the settings like `line_prefix` stay
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let mut c = OutputFormatter::new().fg().red().text_str("loading");
+    /// c.clear();
+    /// assert_eq!(OutputFormatter::new(), c);
+    /// ```
+    pub fn clear(&mut self) {
+        self.output.clear();
+        self.saved_styles.clear();
+    }
+
+    /// remove the texts built so far, but keep the colors and styles that are active at the end
+    /// so the next text uses them again, e.g. to redraw a status line with new content
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let mut c = OutputFormatter::new().fg().green().text_str("10%");
+    /// c.clear_keep_style();
+    /// let c = c.text_str("20%");
+    /// assert_eq!("\u{1b}[32m20%\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn clear_keep_style(&mut self) {
+        let codes = self.active_codes().codes();
+        self.clear();
+        if !codes.is_empty() {
+            self.output.push(codes);
+        }
+    }
+
+    /// remember the current colors and styles, to go back to them with `restore_style`
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new()
+    ///     .fg().red().text_str("error: ")
+    ///     .save_style().fg().blue().text_str("file.rs").restore_style()
+    ///     .text_str(" not found");
+    /// ```
+    pub fn save_style(mut self) -> Self {
+        let codes = self.active_codes().codes();
+        self.saved_styles.push(codes);
+        self
+    }
+
+    /// go back to the colors and styles of the matching `save_style`,
+    /// without a saved state everything is reset
+    pub fn restore_style(mut self) -> Self {
+        let mut codes = vec!["0".to_string()];
+        codes.extend(self.saved_styles.pop().unwrap_or_default());
+        self.output.push(codes);
+        self
+    }
+
+    /// Apply bold to the following text
+    pub fn bold(self) -> Self {
+        self.push_style(Style::Bold)
+    }
+
+    /// Apply dim (faint) to the following text
+    pub fn dim(self) -> Self {
+        self.push_style(Style::Dim)
+    }
+
+    /// Apply italic to the following text
+    pub fn italic(self) -> Self {
+        self.push_style(Style::Italic)
+    }
+
+    /// Apply underline to the following text
+    pub fn underline(self) -> Self {
+        self.push_style(Style::Underline)
+    }
+
+    /// Swap foreground and background of the following text
+    pub fn reverse(self) -> Self {
+        self.push_style(Style::Reverse)
+    }
+
+    /// Apply strikethrough to the following text
+    pub fn strikethrough(self) -> Self {
+        self.push_style(Style::Strikethrough)
+    }
+
+    /// Apply slow blinking to the following text
+    pub fn blink(self) -> Self {
+        self.push_style(Style::Blink)
+    }
+
+    /// Apply rapid blinking to the following text
+    pub fn blink_rapid(self) -> Self {
+        self.push_style(Style::BlinkRapid)
+    }
+
+    /// Conceal the following text, it is still part of the output but the terminal does not show it
+    pub fn hidden(self) -> Self {
+        self.push_style(Style::Hidden)
+    }
+
+    /// Apply double underline to the following text
+    pub fn double_underline(self) -> Self {
+        self.push_style(Style::DoubleUnderline)
+    }
+
+    /// Apply overline to the following text
+    pub fn overline(self) -> Self {
+        self.push_style(Style::Overline)
+    }
+
+    /// Switch off bold (and dim, which shares the same code) for the following text
+    pub fn bold_off(self) -> Self {
+        self.push_style_off(Style::Bold)
+    }
+
+    /// Switch off italic for the following text
+    pub fn italic_off(self) -> Self {
+        self.push_style_off(Style::Italic)
+    }
+
+    /// Switch off underline for the following text
+    pub fn underline_off(self) -> Self {
+        self.push_style_off(Style::Underline)
+    }
+
+    /// Draw a frame around the following text
+    /// only few terminals support this, most of them ignore it
+    pub fn framed(self) -> Self {
+        self.push_style(Style::Framed)
+    }
+
+    /// Draw a circle around the following text
+    /// only few terminals support this, most of them ignore it
+    pub fn encircled(self) -> Self {
+        self.push_style(Style::Encircled)
+    }
+
+    /// Apply black or white as foreground, whichever is better readable on the current background
+    /// does nothing if no background is set
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().bg().blue().auto_fg().text_str("readable");
+    /// assert_eq!("\u{1b}[37;44mreadable\u{1b}[39;49m", c.print());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn auto_fg(mut self) -> Self {
+        if let Some(bg) = self.last_bg() {
+            self.output.push(Color::new(Color::best_text_for(&bg), COLORS::None).apply());
+        }
+        self
+    }
+
+    #[cfg(feature = "std")]
+    fn last_bg(&self) -> Option<COLORS> {
+        for code in self.codes().rev() {
+            // a reset or the default background leave the terminal background showing
+            if code == "0" || code == "49" {
+                return None;
+            }
+            if Color::is_bg_code(code) {
+                return Color::color_from_code(code);
+            }
+        }
+        None
+    }
+
+    /// Turn every color into the gray of the same luminance
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().red().text_str("error").grayscale();
+    /// assert_eq!("\u{1b}[38;2;27;27;27merror\u{1b}[39;49m", c.print());
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn grayscale(mut self) -> Self {
+        for v in self.output.iter_mut() {
+            if !matches!(Entry::of(v), Entry::Codes(_)) {
+                continue;
+            }
+            for code in v.iter_mut() {
+                let rgb = Color::color_from_code(code).and_then(|c| Color::to_rgb(&c));
+                if let Some((r, g, b)) = rgb {
+                    let luminance = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+                    let gray = (luminance + 0.5) as u8;
+                    let prefix = if Color::is_bg_code(code) { '4' } else { '3' };
+                    *code = format!("{}8;2;{};{};{}", prefix, gray, gray, gray);
+                }
+            }
+        }
+        self
+    }
+
+    /// Turn every color into its negative, see `Color::invert_rgb`
+    /// unlike `invert` this keeps foreground and background on their side
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().rgb(10, 20, 30).text_str("x").negative();
+    /// assert_eq!("\u{1b}[38;2;245;235;225mx\u{1b}[39;49m", c.print());
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn negative(mut self) -> Self {
+        for v in self.output.iter_mut() {
+            if !matches!(Entry::of(v), Entry::Codes(_)) {
+                continue;
+            }
+            for code in v.iter_mut() {
+                if let Some(COLORS::Rgb(r, g, b)) = Color::color_from_code(code).map(|c| Color::invert_rgb(&c)) {
+                    let prefix = if Color::is_bg_code(code) { '4' } else { '3' };
+                    *code = format!("{}8;2;{};{};{}", prefix, r, g, b);
+                }
+            }
+        }
+        self
+    }
+
+    /// Swap foreground and background of every segment, colors set by earlier segments are
+    /// swapped as well, an unset side becomes the terminal default on the other side
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().fg().white().bg().green().text_str("selected").invert();
+    /// assert_eq!("\u{1b}[32;47mselected\u{1b}[39;49m", c.print());
+    /// ```
+    pub fn invert(mut self) -> Self {
+        let mut output: Vec<Vec<String>> = vec![];
+        let mut pending: Vec<String> = vec![];
+        let mut active = ActiveCodes::default();
+        for v in self.output.drain(..) {
+            if let Entry::Codes(codes) = Entry::of(&v) {
+                pending.extend(codes.iter().cloned());
+            } else {
+                if !pending.is_empty() {
+                    output.push(OutputFormatter::invert_codes(&pending, &mut active));
+                    pending = vec![];
+                }
+                output.push(v);
+            }
+        }
+        if !pending.is_empty() {
+            output.push(OutputFormatter::invert_codes(&pending, &mut active));
+        }
+        self.output = output;
+        self
+    }
+
+    /// the codes with the colors replaced by the swapped colors of the whole state after them
+    fn invert_codes(codes: &[String], active: &mut ActiveCodes) -> Vec<String> {
+        active.changed(codes);
+        let mut inverted: Vec<String> = codes
+            .iter()
+            .filter(|c| !Color::is_fg_code(c) && !Color::is_bg_code(c))
+            .cloned()
+            .collect();
+        if codes.iter().any(|c| Color::is_fg_code(c) || Color::is_bg_code(c)) {
+            let swap = |code: &str, prefix: &str, default: &str| match code {
+                "" => default.to_string(),
+                code => format!("{}{}", prefix, &code[1..]),
+            };
+            inverted.push(swap(&active.bg, "3", "39"));
+            inverted.push(swap(&active.fg, "4", "49"));
+        }
+        inverted
+    }
+
+    /// Apply several styles at once
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::style::StyleFlags;
+    /// let c = OutputFormatter::new().fg().red().style(StyleFlags::BOLD | StyleFlags::ITALIC).text_str("Hi");
+    /// ```
+    pub fn style(mut self, flags: StyleFlags) -> Self {
+        let codes: Vec<String> = flags.styles().map(|s| s.on_code()).collect();
+        if !codes.is_empty() {
+            self.output.push(codes);
+        }
+        self
+    }
+
+    /// set the foreground color and several styles in one call
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// use terminal_color_builder::style::StyleFlags;
+    /// let c = OutputFormatter::new().fg_styled(COLORS::Red, StyleFlags::BOLD).text_str("failed");
+    /// assert_eq!("\u{1b}[31;1mfailed\u{1b}[39;49;22m", c.print());
+    /// ```
+    pub fn fg_styled(self, color: COLORS, flags: StyleFlags) -> Self {
+        self.custom(color, COLORS::None).style(flags)
+    }
+
+    fn push_style(mut self, style: Style) -> Self {
+        self.output.push(vec![style.on_code()]);
+        self
+    }
+
+    fn push_style_off(mut self, style: Style) -> Self {
+        self.output.push(vec![style.off_code()]);
+        self
+    }
+
+    /// adds a text with a reset in front of it, the colors active before are set again after it
+    fn uncolored(mut self, text: String) -> Self {
+        self.push_uncolored(text);
+        self
+    }
+
+    fn push_uncolored(&mut self, text: String) {
+        let active = self.active_codes();
+        // codes still waiting for a text are set again after the reset
+        while let Some(Entry::Codes(_)) = self.output.last().map(|v| Entry::of(v)) {
+            self.output.pop();
+        }
+        self.output.push(vec!["0".to_string()]);
+        self.push_text(text);
+        let codes = active.codes();
+        if !codes.is_empty() {
+            self.output.push(codes);
+        }
+    }
+
+    /// the colors and styles the next text would be printed with
+    fn active_codes(&self) -> ActiveCodes {
+        let mut active = ActiveCodes::default();
+        for v in &self.output {
+            if let Entry::Codes(codes) = Entry::of(v) {
+                active.changed(codes);
+            }
+        }
+        active
+    }
+
+    /// the output with neighbouring codes and neighbouring texts joined into one entry
+    fn normalized(&self) -> Vec<Vec<String>> {
+        let mut normalized: Vec<Vec<String>> = vec![];
+        for v in &self.output {
+            let last = normalized.last_mut();
+            match (Entry::of(v), last) {
+                (Entry::Codes(codes), Some(last)) if matches!(Entry::of(last), Entry::Codes(_)) => {
+                    last.extend(codes.iter().cloned())
+                },
+                (Entry::Text(text), Some(last)) if matches!(Entry::of(last), Entry::Text(_)) => {
+                    last[1].push_str(text)
+                },
+                (Entry::Codes([]), _) => {},
+                _ => normalized.push(v.clone()),
+            }
+        }
+        normalized
+    }
+
+    fn entries(&self) -> impl DoubleEndedIterator<Item = Entry<'_>> {
+        self.output.iter().map(|v| Entry::of(v))
+    }
+
+    fn codes(&self) -> impl DoubleEndedIterator<Item = &String> {
+        self.entries().flat_map(|e| match e {
+            Entry::Codes(codes) => codes.iter(),
+            _ => [].iter(),
+        })
+    }
+
+    fn texts(&self) -> impl Iterator<Item = &str> {
+        self.entries().filter_map(|e| match e {
+            Entry::Text(text) => Some(text),
+            _ => None,
+        })
+    }
+
+    /// the escape sequence resetting all colors and the used styles at the end of the output
+    pub fn final_reset(&self) -> String {
+        if self.reset_policy == RenderReset::Full {
+            return Color::format(&["0".to_string()]);
+        }
+        let used: Vec<Style> = Style::ALL
+            .iter()
+            .copied()
+            .filter(|style| {
+                let on = style.on_code();
+                self.codes().any(|c| *c == on)
+            })
+            .collect();
+        Color::format(&Color::with_styles(COLORS::None, COLORS::None, &used).unapply())
+    }
+
+    fn push_text(&mut self, message: String) {
+        self.output.push(vec!["#text#".to_string(), message]);
+    }
+}
+
+/// Two builders are equal if they produce the same styling and text, no matter if the colors
+/// were set in one or in several calls
+///
+/// Example
+/// ```
+/// use terminal_color_builder::*;
+/// use terminal_color_builder::color::COLORS;
+/// let a = OutputFormatter::new().fg().red().bg().blue().text_str("a");
+/// let b = OutputFormatter::new().custom(COLORS::Red, COLORS::Blue).text_str("a");
+/// assert_eq!(a, b);
+/// ```
+impl PartialEq for OutputFormatter {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+            && self.line_prefix == other.line_prefix
+            && self.reset_policy == other.reset_policy
+            && self.line_ending == other.line_ending
+            && self.indent == other.indent
+    }
+}
+
+/// the number of terminal columns a text takes up, wide characters count as two
+/// when the `unicode-width` feature is enabled
+fn text_width(text: &str) -> usize {
+    #[cfg(feature = "unicode-width")]
+    return UnicodeWidthStr::width(text);
+    #[cfg(not(feature = "unicode-width"))]
+    return text.chars().count();
+}
+
+/// a hash of the key that is the same on every platform and in every run (FNV-1a)
+fn hash_key(key: &str) -> u32 {
+    key.bytes().fold(0x811c_9dc5, |hash, b| (hash ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
+/// moves the foreground codes in front of the background codes, no matter in which order the
+/// colors were set, parsers of the output may expect this order. Only the places of the color
+/// codes are swapped, the styles and resets stay where they are
+fn fg_before_bg<S: AsRef<str>>(codes: &mut [S]) {
+    // a reset in between would change what the codes mean, so only codes between resets are moved
+    for run in codes.split_mut(|c| c.as_ref() == "0") {
+        let slots: Vec<usize> = (0..run.len())
+            .filter(|&i| Color::is_fg_code(run[i].as_ref()) || Color::is_bg_code(run[i].as_ref()))
+            .collect();
+        for i in 1..slots.len() {
+            let mut j = i;
+            while j > 0 && Color::is_bg_code(run[slots[j - 1]].as_ref()) && Color::is_fg_code(run[slots[j]].as_ref()) {
+                run.swap(slots[j - 1], slots[j]);
+                j -= 1;
+            }
+        }
+    }
+}
+
+/// checks if an SGR code only resets colors or styles
+fn is_off_code(code: &str) -> bool {
+    code == "0" || code == "39" || code == "49" || Style::ALL.iter().any(|s| s.off_code() == code)
+}
+
+/// The kinds of entries the builder output consists of
+enum Entry<'a> {
+    /// SGR codes applied to the following text
+    Codes(&'a [String]),
+    /// text printed with the currently applied codes
+    Text(&'a str),
+    /// an escape sequence that is passed through as is
+    Raw(&'a str),
+    /// a hyperlink for the following text
+    Link(&'a str),
+    /// the following text has a transparent background (`true`) or foreground (`false`),
+    /// this has no escape sequence and is only used by the other renderers
+    Transparent(bool),
+}
+
+impl<'a> Entry<'a> {
+    fn of(v: &'a [String]) -> Self {
+        let value = v.get(1).map(String::as_str).unwrap_or("");
+        match v.first().map(String::as_str) {
+            Some("#text#") => Entry::Text(value),
+            Some("#raw#") => Entry::Raw(value),
+            Some("#link#") => Entry::Link(value),
+            Some("#transparent#") => Entry::Transparent(value == "bg"),
+            _ => Entry::Codes(v),
+        }
+    }
+}
+
+/// The codes active in the terminal while rendering, used to leave out codes without effect
+#[derive(Default)]
+struct ActiveCodes {
+    fg: String,
+    bg: String,
+    styles: Vec<Style>,
+}
+
+impl ActiveCodes {
+    /// applies the codes and returns the ones that changed something
+    fn changed(&mut self, codes: &[String]) -> Vec<String> {
+        let mut changed: Vec<String> = vec![];
+        for c in codes {
+            if c == "0" {
+                *self = ActiveCodes::default();
+                changed.push(c.clone());
+            } else if Color::is_fg_code(c) || Color::is_bg_code(c) {
+                let active = if Color::is_bg_code(c) { &mut self.bg } else { &mut self.fg };
+                if active != c {
+                    *active = c.clone();
+                    changed.push(c.clone());
+                }
+            } else if let Some(style) = Style::from_on_code(c) {
+                if !self.styles.contains(&style) {
+                    self.styles.push(style);
+                    changed.push(c.clone());
+                }
+            } else {
+                // everything else turns something off, so it always has to be emitted
+                self.styles.retain(|s| s.off_code() != *c);
+                changed.push(c.clone());
+            }
+        }
+        changed
+    }
+
+    /// the codes needed to set up this state from a reset terminal
+    fn codes(&self) -> Vec<String> {
+        let mut codes: Vec<String> = vec![];
+        for c in [&self.fg, &self.bg] {
+            if !c.is_empty() && c != "39" && c != "49" {
+                codes.push(c.clone());
+            }
+        }
+        codes.extend(self.styles.iter().map(Style::on_code));
+        codes
+    }
+}
+
+/// Inserts the indent, the line prefix and the line ending into the texts, remembering if the
+/// last text ended a line
+struct LinePrefixer<'a> {
+    prefix: Option<&'a str>,
+    line_ending: LineEnding,
+    indent: usize,
+    at_line_start: bool,
+    after_cr: bool,
+    skip_indent: bool,
+}
+
+impl<'a> LinePrefixer<'a> {
+    fn new(prefix: Option<&'a str>, line_ending: LineEnding, indent: usize) -> Self {
+        LinePrefixer {
+            prefix,
+            line_ending,
+            indent,
+            at_line_start: true,
+            after_cr: false,
+            skip_indent: false,
+        }
+    }
+
+    /// the indent of the line that is about to start, for printing it before the codes of the
+    /// text, `apply` then leaves it out
+    fn take_indent(&mut self) -> Option<String> {
+        if !self.at_line_start || self.skip_indent || self.indent == 0 {
+            return None;
+        }
+        self.skip_indent = true;
+        Some(" ".repeat(self.indent))
+    }
+
+    /// `colors` are the codes active for the text, they are switched off around the indent
+    fn apply<'t>(&mut self, text: &'t str, colors: &[String]) -> Cow<'t, str> {
+        if self.prefix.is_none() && self.line_ending == LineEnding::Lf && self.indent == 0 {
+            return Cow::Borrowed(text);
+        }
+        let mut prefixed = String::new();
+        for c in text.chars() {
+            if self.at_line_start && self.indent > 0 && !self.skip_indent {
+                if colors.is_empty() {
+                    prefixed.push_str(&" ".repeat(self.indent));
+                } else {
+                    Color::format_into(&mut prefixed, &["0".to_string()]);
+                    prefixed.push_str(&" ".repeat(self.indent));
+                    Color::format_into(&mut prefixed, colors);
+                }
+            }
+            if let (true, Some(prefix)) = (self.at_line_start, self.prefix) {
+                prefixed.push_str(prefix);
+            }
+            // a newline that already is a `\r\n` is not terminated twice
+            if c == '\n' && self.line_ending == LineEnding::Crlf && !self.after_cr {
+                prefixed.push('\r');
+            }
+            prefixed.push(c);
+            self.at_line_start = c == '\n';
+            self.after_cr = c == '\r';
+            self.skip_indent = false;
+        }
+        Cow::Owned(prefixed)
+    }
+}
+
+/// Allows using `write!` on the builder, the written text keeps the last set color
+///
+/// Example
+/// ```
+/// use core::fmt::Write;
+/// use terminal_color_builder::*;
+/// let mut c = OutputFormatter::new().fg().green();
+/// write!(c, "{} items", 3).unwrap();
+/// ```
+impl fmt::Write for OutputFormatter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_text(s.to_string());
+        Ok(())
+    }
+}
+
+/// Allows streaming bytes into the builder, e.g. the output of a child process
+/// every write is added as text in the last set color, invalid UTF-8 is replaced with `�`
+///
+/// Example
+/// ```
+/// use std::io::Write;
+/// use terminal_color_builder::*;
+/// let mut c = OutputFormatter::new().fg().red();
+/// c.write_all(b"failed").unwrap();
+/// assert_eq!("\u{1b}[31mfailed\u{1b}[39;49m", c.print());
+/// ```
+#[cfg(feature = "std")]
+impl std::io::Write for OutputFormatter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !buf.is_empty() {
+            self.push_text(String::from_utf8_lossy(buf).into_owned());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// OutputColor cannot be created on its own. Usage through OutputFormatter
+impl OutputColor {
+    /// Apply black to current context
+    pub fn black(&mut self) -> OutputFormatter {
+        self.color(COLORS::Black)
+    }
+
+    /// Apply red to current context
+    pub fn red(&mut self) -> OutputFormatter {
+        self.color(COLORS::Red)
+    }
+
+    /// Apply green to current context
+    pub fn green(&mut self) -> OutputFormatter {
+        self.color(COLORS::Green)
+    }
+
+    /// Apply yellow to current context
+    pub fn yellow(&mut self) -> OutputFormatter {
+        self.color(COLORS::Yellow)
+    }
+
+    /// Apply blue to current context
+    pub fn blue(&mut self) -> OutputFormatter {
+        self.color(COLORS::Blue)
+    }
+
+    /// Apply magenta to current context
+    pub fn magenta(&mut self) -> OutputFormatter {
+        self.color(COLORS::Magenta)
+    }
+
+    /// Apply cyan to current context
+    pub fn cyan(&mut self) -> OutputFormatter {
+        self.color(COLORS::Cyan)
+    }
+
+    /// Apply white to current context
+    pub fn white(&mut self) -> OutputFormatter {
+        self.color(COLORS::White)
+    }
+
+    /// Apply the terminal default color to current context
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let c = OutputFormatter::new().bg().red().text_str("a").bg().default_color().text_str("b");
+    /// ```
+    pub fn default_color(&mut self) -> OutputFormatter {
+        self.color(COLORS::Default)
+    }
+
+    /// Apply custom color by hex value to current context
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let white = OutputFormatter::new().fg().hex("#fff");
+    /// let rnd = OutputFormatter::new().fg().hex("#ab1346");
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn hex(&mut self, color: &str) -> OutputFormatter {
+        self.color(COLORS::HEX(color.to_string()))
+    }
+
+    /// Apply custom color by hex value to current context, or return why the hex value is invalid
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::HexError;
+    /// assert!(OutputFormatter::new().fg().try_hex("#ab1346").is_ok());
+    /// assert_eq!(Some(HexError::Empty), OutputFormatter::new().fg().try_hex("  ").err());
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn try_hex(&mut self, color: &str) -> Result<OutputFormatter, HexError> {
+        Color::parse_hex(color)?;
+        Ok(self.color(COLORS::HEX(color.to_string())))
+    }
+
+    /// Apply custom color by rgb value to current context
+    #[cfg(feature = "truecolor")]
+    pub fn rgb(&mut self, r: u8, g: u8, b: u8) -> OutputFormatter {
+        self.color(COLORS::Rgb(r, g, b))
+    }
+
+    /// Apply custom color by a packed `0xRRGGBB` value to current context
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let orange = OutputFormatter::new().fg().rgb_u32(0xff8000);
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn rgb_u32(&mut self, value: u32) -> OutputFormatter {
+        self.color(Color::from_rgb_u32(value))
+    }
+
+    /// Apply transparency to current context, the terminal keeps the color it has
+    /// and renderers like `HtmlRenderer` make the color explicitly transparent
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::render::HtmlRenderer;
+    /// let c = OutputFormatter::new().bg().transparent().text_str("x");
+    /// assert_eq!("<span style=\"background: transparent\">x</span>", c.render_with(&HtmlRenderer));
+    /// ```
+    pub fn transparent(&mut self) -> OutputFormatter {
+        self.color(COLORS::Transparent)
+    }
+
+    /// Apply an entry of the 256-color palette to current context
+    pub fn ansi256(&mut self, n: u8) -> OutputFormatter {
+        self.color(COLORS::Ansi256(n))
+    }
+
+    /// Apply custom color by an rgb tuple to current context
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// let orange = (255, 128, 0);
+    /// let c = OutputFormatter::new().fg().rgb_tuple(orange).text_str("warning");
+    /// ```
+    #[cfg(feature = "truecolor")]
+    pub fn rgb_tuple(&mut self, rgb: impl Into<(u8, u8, u8)>) -> OutputFormatter {
+        let (r, g, b) = rgb.into();
+        self.rgb(r, g, b)
+    }
+
+    /// Apply any color to current context
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::color::COLORS;
+    /// let c = OutputFormatter::new().fg().color(COLORS::Magenta).text_str("Hi");
+    /// ```
+    pub fn color(&mut self, c: COLORS) -> OutputFormatter {
+        self.colorize(c, COLORS::None)
+    }
+
+    /// Apply a custom foreground and background, this ignores the current context
+    /// so `bg().custom(fg, bg)` applies them the same way as `fg().custom(fg, bg)`
+    pub fn custom(&mut self, fg: COLORS, bg: COLORS) -> OutputFormatter {
+        self.for_style = StyleType::Both;
+        self.colorize(fg, bg)
+    }
+
+    /// In the foreground and background context `fg` is the color applied to that context
+    /// and `bg` is applied to the other one. `COLORS::Default` emits the reset to the terminal
+    /// default (`39`/`49`), `COLORS::None` emits nothing and keeps the active color
+    fn colorize(&mut self, fg: COLORS, bg: COLORS) -> OutputFormatter {
+        let (fg, bg) = match &self.for_style {
+            StyleType::FG | StyleType::Both => (fg, bg),
+            StyleType::BG => (bg, fg),
+        };
+        let transparent = [(&fg, "fg"), (&bg, "bg")]
+            .iter()
+            .filter(|(color, _)| matches!(color, COLORS::Transparent))
+            .map(|(_, side)| vec!["#transparent#".to_string(), side.to_string()])
+            .collect::<Vec<_>>();
+        self.formatter.output.push(Color::new(fg, bg).apply());
+        self.formatter.output.extend(transparent);
+        self.formatter.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// an environment with only the given variables, for the color detection tests
+    fn fake_env(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+    }
+
+    /// a terminal that supports the given colors, the 88 colors go with the 256 colors
+    #[cfg(any(feature = "truecolor", feature = "log"))]
+    fn fake_support(truecolor: bool, ansi256: bool, enabled: bool) -> mode::ColorSupport {
+        mode::ColorSupport {
+            enabled,
+            basic: enabled,
+            ansi88: ansi256,
+            ansi256,
+            truecolor,
+        }
+    }
+
+    #[test]
+    pub fn test_prelude_exports() {
+        use crate::prelude::*;
+        let c = OutputFormatter::new().custom(COLORS::White, COLORS::Green).text_str("Hi");
+        assert_eq!(Color::new(COLORS::White, COLORS::Green).print("Hi".to_string()), c.print());
+    }
+
+    #[test]
+    pub fn test_create_color() {
+        let c = Color::new(COLORS::Green, COLORS::White);
+        assert_eq!("\u{1b}[32;47mhello\u{1b}[39;49m", c.print("hello".to_string()));
+    }
+
+    #[test]
+    pub fn test_create_only_bg_color() {
+        let c = Color::new(COLORS::None, COLORS::White);
+        assert_eq!("\u{1b}[47mhello\u{1b}[39;49m", c.print("hello".to_string()));
+    }
+
+    #[test]
+    pub fn test_create_only_fg_color() {
+        let c = Color::new(COLORS::Black, COLORS::None);
+        assert_eq!("\u{1b}[30mhello\u{1b}[39;49m", c.print("hello".to_string()));
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_write_color() {
+        for c in [
+            Color::new(COLORS::Green, COLORS::White),
+            Color::new(COLORS::HEX("#ab1346".to_string()), COLORS::None),
+            Color::new(COLORS::None, COLORS::None),
+        ] {
+            let mut out: Vec<u8> = vec![];
+            c.write(&mut out, "hello").unwrap();
+            assert_eq!(c.print("hello".to_string()).into_bytes(), out);
+        }
+    }
+
+    #[test]
+    pub fn test_color_builder_green_bg_white_fg() {
+        let c = OutputFormatter::new().fg().white().bg().green().text("Hi".to_string());
+        assert_eq!("\u{1b}[37;42mHi\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_color_builder_green_bg_white_fg_custom() {
+        let c = OutputFormatter::new().custom(COLORS::White, COLORS::Green).text("Hi".to_string());
         assert_eq!("\u{1b}[37;42mHi\u{1b}[39;49m", c.print());
     }
 
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_color_builder_green_bg_white_fg_custom_combination() {
+        let c = OutputFormatter::new()
+            .custom(COLORS::None, COLORS::Default)
+            .text("H".to_string())
+            .fg()
+            .hex("#fff")
+            .bg()
+            .black()
+            ;
+        assert_eq!("\u{1b}[49mH\u{1b}[38;2;255;255;255;40m\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_write_macro_inherits_color() {
+        use core::fmt::Write;
+        let mut c = OutputFormatter::new().fg().red();
+        write!(c, "{} items", 3).unwrap();
+        writeln!(c, "!").unwrap();
+        assert_eq!("\u{1b}[31m3 items!\n\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_print_merges_same_color_segments() {
+        let mut c = OutputFormatter::new();
+        for _ in 0..100 {
+            c = c.fg().red().text_str("#");
+        }
+        let unmerged_len = 100 * ("\u{1b}[31m".len() + 1) + "\u{1b}[39;49m".len();
+        let printed = c.print();
+        assert!(printed.len() < unmerged_len);
+        assert_eq!(format!("\u{1b}[31m{}\u{1b}[39;49m", "#".repeat(100)), printed);
+    }
+
+    #[test]
+    pub fn test_render_minified_skips_unchanged_codes() {
+        let c = OutputFormatter::new()
+            .fg().white().bg().red().text_str("a")
+            .fg().white().bg().blue().text_str("b")
+            .fg().white().bg().green().text_str("c");
+        assert_eq!("\u{1b}[37;41ma\u{1b}[37;44mb\u{1b}[37;42mc\u{1b}[39;49m", c.print());
+        assert_eq!("\u{1b}[37;41ma\u{1b}[44mb\u{1b}[42mc\u{1b}[39;49m", c.render_minified());
+        assert!(c.render_minified().len() < c.print().len());
+    }
+
+    #[test]
+    pub fn test_render_minified_keeps_resets() {
+        let c = OutputFormatter::new()
+            .fg().red().text_str("a")
+            .custom(COLORS::Default, COLORS::None).text_str("b");
+        assert_eq!("\u{1b}[31ma\u{1b}[39mb\u{1b}[39;49m", c.render_minified());
+    }
+
+    #[test]
+    pub fn test_default_color_in_both_scopes() {
+        let c = OutputFormatter::new().bg().default_color().text_str("a");
+        assert_eq!("\u{1b}[49ma\u{1b}[39;49m", c.print());
+        let c = OutputFormatter::new().fg().default_color().text_str("a");
+        assert_eq!("\u{1b}[39ma\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_visible_len_ignores_color_codes() {
+        let c = OutputFormatter::new().fg().red().text_str("ab").bg().blue().text_str("日本");
+        assert_eq!(4, c.visible_len());
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    pub fn test_display_width_cjk() {
+        let c = OutputFormatter::new().fg().red().text_str("漢字").fg().blue().text_str("ab");
+        assert_eq!(4, c.visible_len());
+        assert_eq!(6, c.display_width());
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    pub fn test_display_width_zero_width_joiner() {
+        let c = OutputFormatter::new().fg().red().text_str("\u{1F468}\u{200D}\u{1F469}");
+        assert_eq!(3, c.visible_len());
+        assert_eq!(2, c.display_width());
+    }
+
+    #[test]
+    pub fn test_double_underline() {
+        let c = OutputFormatter::new().fg().red().double_underline().text_str("a");
+        assert_eq!("\u{1b}[31;21ma\u{1b}[39;49;24m", c.print());
+    }
+
+    #[test]
+    pub fn test_overline_with_double_underline() {
+        let c = OutputFormatter::new().overline().text_str("a").double_underline().text_str("b");
+        assert_eq!("\u{1b}[53ma\u{1b}[21mb\u{1b}[39;49;24;55m", c.print());
+        assert_eq!(c.print(), c.render_minified());
+    }
+
+    #[test]
+    pub fn test_blink_with_color() {
+        let c = OutputFormatter::new().fg().red().blink().text_str("ALERT");
+        assert_eq!("\u{1b}[31;5mALERT\u{1b}[39;49;25m", c.print());
+    }
+
+    #[test]
+    pub fn test_both_blinks_reset_once() {
+        let c = OutputFormatter::new().blink().blink_rapid().text_str("a");
+        assert_eq!("\u{1b}[5;6ma\u{1b}[39;49;25m", c.print());
+    }
+
+    #[test]
+    pub fn test_hidden_text() {
+        let c = OutputFormatter::new().text_str("password: ").hidden().text_str("secret");
+        assert_eq!("password: \u{1b}[8msecret\u{1b}[39;49;28m", c.print());
+        assert_eq!("password: secret", c.plain());
+        assert_eq!(16, c.visible_len());
+    }
+
+    #[test]
+    pub fn test_style_flags() {
+        let c = OutputFormatter::new().style(StyleFlags::BOLD | StyleFlags::UNDERLINE).text_str("a");
+        assert_eq!("\u{1b}[1;4ma\u{1b}[39;49;22;24m", c.print());
+        let chained = OutputFormatter::new().bold().underline().text_str("a");
+        assert_eq!(chained.print(), c.print());
+    }
+
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_hex_color_channel_order() {
+        let c = Color::new(COLORS::HEX("#ff8000".to_string()), COLORS::None);
+        assert_eq!("\u{1b}[38;2;255;128;0mhello\u{1b}[39;49m", c.print("hello".to_string()));
+        // the digits used to be read from the end, which printed #ab1346 as #6431ba
+        let c = Color::new(COLORS::HEX("#ab1346".to_string()), COLORS::None);
+        assert_eq!("\u{1b}[38;2;171;19;70mhello\u{1b}[39;49m", c.print("hello".to_string()));
+    }
+
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_rgb_color() {
+        let c = Color::new(COLORS::Rgb(255, 128, 0), COLORS::Rgb(0, 0, 0));
+        assert_eq!("\u{1b}[38;2;255;128;0;48;2;0;0;0mhello\u{1b}[39;49m", c.print("hello".to_string()));
+    }
+
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_grayscale() {
+        let c = OutputFormatter::new()
+            .fg().red()
+            .bg().hex("#fff")
+            .text_str("a")
+            .bold()
+            .text_str("b")
+            .grayscale();
+        assert_eq!("\u{1b}[38;2;27;27;27;48;2;255;255;255ma\u{1b}[1mb\u{1b}[39;49;22m", c.print());
+    }
+
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_invert() {
+        let c = OutputFormatter::new()
+            .fg().hex("#fff")
+            .bg().red()
+            .text_str("a")
+            .fg().blue()
+            .text_str("b")
+            .invert();
+        assert_eq!("\u{1b}[31;48;2;255;255;255ma\u{1b}[31;44mb\u{1b}[39;49m", c.print());
+        // the background of the first segment stays the foreground of the second one
+        let c = OutputFormatter::new().fg().red().text_str("a").bg().blue().text_str("b").invert();
+        assert_eq!("\u{1b}[39;41ma\u{1b}[34;41mb\u{1b}[39;49m", c.print());
+        let c = OutputFormatter::new().fg().red().text_str("a").reset().bg().blue().text_str("b").invert();
+        assert_eq!("\u{1b}[39;41ma\u{1b}[0;34;49mb\u{1b}[39;49m", c.print());
+    }
+
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_palette_values() {
+        use crate::palette::Palette;
+        assert_eq!(COLORS::Rgb(0xbd, 0x93, 0xf9), Palette::dracula().accent());
+        assert_eq!(COLORS::Rgb(0xdc, 0x32, 0x2f), Palette::solarized().error());
+        let c = OutputFormatter::new().custom(Palette::nord().success(), COLORS::None).text_str("ok");
+        assert_eq!("\u{1b}[38;2;163;190;140mok\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_to_bytes() {
+        let c = OutputFormatter::new().fg().green().text_str("ok").bg().red().text_str("!");
+        assert_eq!(c.print().into_bytes(), c.to_bytes());
+    }
+
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_from_ansi_round_trip() {
+        let c = OutputFormatter::new()
+            .fg().hex("#ff8000")
+            .bg().blue()
+            .text_str("a")
+            .bold()
+            .underline()
+            .text_str("b")
+            .custom(COLORS::Default, COLORS::Green)
+            .text_str("c");
+        let printed = c.print();
+        assert_eq!(printed, OutputFormatter::from_ansi(&printed).print());
+        assert_eq!("abc", OutputFormatter::from_ansi(&printed).plain());
+    }
+
+    #[test]
+    pub fn test_from_ansi_passes_unknown_sequences() {
+        let s = "\u{1b}[2K\u{1b}]8;;https://example.com\u{7}link\u{1b}]8;;\u{7}\u{1b}[38;5;196m!";
+        let c = OutputFormatter::from_ansi(s);
+        assert_eq!("link!", c.plain());
+        assert_eq!(format!("{}\u{1b}[39;49m", s), c.print());
+    }
+
+    #[test]
+    pub fn test_char_colors() {
+        let c = OutputFormatter::new().char_colors("abc", &[COLORS::Red, COLORS::Green, COLORS::Blue]);
+        assert_eq!("\u{1b}[31ma\u{1b}[32mb\u{1b}[34mc\u{1b}[39;49m", c.print());
+        let empty = OutputFormatter::new().char_colors("", &[COLORS::Red]).char_colors("abc", &[]);
+        assert_eq!("\u{1b}[39;49m", empty.print());
+    }
+
+    #[test]
+    pub fn test_color_macro() {
+        assert_eq!(
+            "\u{1b}[31;1merror: \u{1b}[0;39mdetails\u{1b}[39;49;22m",
+            crate::color!(red bold "error: ", default "details")
+        );
+        let chained = OutputFormatter::new().fg().white().bg().blue().underline().text_str("x");
+        assert_eq!(chained.print(), crate::color!(white on_blue underline "x"));
+    }
+
+    #[test]
+    pub fn test_color_macro_plain_text() {
+        assert_eq!("plain\u{1b}[39;49m", crate::color!("plain"));
+        assert_eq!("\u{1b}[32ma\u{1b}[0mb\u{1b}[39;49m", crate::color!(green "a", "b",));
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_best_text_color() {
+        assert_eq!(COLORS::White, Color::best_text_for(&COLORS::Black));
+        assert_eq!(COLORS::White, Color::best_text_for(&COLORS::HEX("#1e1e2e".to_string())));
+        assert_eq!(COLORS::Black, Color::best_text_for(&COLORS::HEX("#ffff80".to_string())));
+        assert!(Color::contrast_ratio(&COLORS::Black, &COLORS::White) > Color::contrast_ratio(&COLORS::Black, &COLORS::Blue));
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_auto_fg() {
+        let c = OutputFormatter::new().bg().hex("#eee").auto_fg().text_str("a");
+        assert_eq!("\u{1b}[30;48;2;238;238;238ma\u{1b}[39;49m", c.print());
+        let c = OutputFormatter::new().fg().red().auto_fg().text_str("a");
+        assert_eq!("\u{1b}[31ma\u{1b}[39;49m", c.print());
+        let c = OutputFormatter::new().bg().hex("#eee").bg().default_color().auto_fg().text_str("a");
+        assert_eq!("\u{1b}[48;2;238;238;238;49ma\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_scopes_with_default_and_none() {
+        let fg_default = OutputFormatter::new().fg().default_color().text_str("a");
+        assert_eq!("\u{1b}[39ma\u{1b}[39;49m", fg_default.print());
+        let fg_none = OutputFormatter::new().fg().custom(COLORS::None, COLORS::None).text_str("a");
+        assert_eq!("a\u{1b}[39;49m", fg_none.print());
+        let bg_default = OutputFormatter::new().bg().default_color().text_str("a");
+        assert_eq!("\u{1b}[49ma\u{1b}[39;49m", bg_default.print());
+        let bg_none = OutputFormatter::new().bg().custom(COLORS::None, COLORS::None).text_str("a");
+        assert_eq!("a\u{1b}[39;49m", bg_none.print());
+        let both_default = OutputFormatter::new().custom(COLORS::Default, COLORS::Default).text_str("a");
+        assert_eq!("\u{1b}[39;49ma\u{1b}[39;49m", both_default.print());
+        let both_none = OutputFormatter::new().custom(COLORS::None, COLORS::None).text_str("a");
+        assert_eq!("a\u{1b}[39;49m", both_none.print());
+    }
+
+    #[test]
+    pub fn test_bg_default_followed_by_fg() {
+        let c = OutputFormatter::new().bg().default_color().fg().red().text_str("a");
+        assert_eq!("\u{1b}[31;49ma\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_custom_in_bg_scope_is_not_swapped() {
+        let c = OutputFormatter::new().bg().custom(COLORS::Red, COLORS::Blue).text_str("a");
+        assert_eq!("\u{1b}[31;44ma\u{1b}[39;49m", c.print());
+        let c = OutputFormatter::new().bg().custom(COLORS::Default, COLORS::None).text_str("a");
+        assert_eq!("\u{1b}[39ma\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_segments() {
+        let c = OutputFormatter::new().fg().red().text_str("a").bg().blue().bold().text_str("b");
+        let segments: Vec<(String, String)> = c.segments().collect();
+        assert_eq!(vec![
+            ("\u{1b}[31m".to_string(), "a".to_string()),
+            ("\u{1b}[44;1m".to_string(), "b".to_string()),
+        ], segments);
+        assert_eq!("\u{1b}[39;49;22m", c.final_reset());
+    }
+
+    #[test]
+    pub fn test_link() {
+        let c = OutputFormatter::new().fg().blue().link("https://docs.rs").text_str("docs").text_str("!");
+        assert_eq!(
+            "\u{1b}[34m\u{1b}]8;;https://docs.rs\u{1b}\\docs\u{1b}]8;;\u{1b}\\!\u{1b}[39;49m",
+            c.print()
+        );
+        assert_eq!("docs!", c.plain());
+    }
+
+    #[test]
+    pub fn test_markdown_renderer() {
+        use crate::render::MarkdownRenderer;
+        let c = OutputFormatter::new()
+            .text_str("see ")
+            .fg().red()
+            .bold()
+            .italic()
+            .link("https://example.com")
+            .text_str("the docs")
+            .reset()
+            .text_str(" or *not*");
+        assert_eq!("see [***the docs***](https://example.com) or \\*not\\*", c.render_with(&MarkdownRenderer));
+    }
+
+    #[cfg(feature = "truecolor")]
     #[test]
-    pub fn test_color_builder_green_bg_white_fg_custom_combination() {
-        let c = OutputFormatter::new()
-            .custom(COLORS::None, COLORS::Default)
-            .text("H".to_string())
-            .fg()
-            .hex("#fff")
-            .bg()
-            .black()
-            ;
-        assert_eq!("\u{1b}[49mH\u{1b}[39;49m", c.print());
+    pub fn test_rgb_tuple() {
+        let c = OutputFormatter::new().fg().rgb_tuple((255, 128, 0)).bg().rgb_tuple((1, 2, 3)).text_str("a");
+        let d = OutputFormatter::new().fg().rgb(255, 128, 0).bg().rgb(1, 2, 3).text_str("a");
+        assert_eq!(d.print(), c.print());
+        assert_eq!("\u{1b}[38;2;255;128;0;48;2;1;2;3ma\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_color_method_matches_named_colors() {
+        let c = OutputFormatter::new().fg().color(COLORS::Magenta).bg().color(COLORS::Cyan).text_str("a");
+        let d = OutputFormatter::new().fg().magenta().bg().cyan().text_str("a");
+        assert_eq!(d.print(), c.print());
     }
 
+    #[test]
+    pub fn test_basic_colors_without_truecolor() {
+        let c = OutputFormatter::new().fg().yellow().bg().black().text_str("a").fg().default_color().text_str("b");
+        assert_eq!("\u{1b}[33;40ma\u{1b}[39mb\u{1b}[39;49m", c.print());
+    }
+
+    #[cfg(feature = "truecolor")]
     #[test]
     pub fn test_color_builder_rainbow() {
         let c = OutputFormatter::new()
@@ -307,4 +2576,986 @@ mod test {
             ;
         assert_eq!("\u{1b}[38;2;255;255;255;41mH\u{1b}[38;2;255;255;255;44me\u{1b}[38;2;255;255;255;43ml\u{1b}[38;2;255;255;255;45ml\u{1b}[38;2;255;255;255;46mo\u{1b}[38;2;255;255;255;42m!\u{1b}[39;49m", c.print());
     }
+
+    #[test]
+    pub fn test_formatter_equality() {
+        let a = OutputFormatter::new().fg().red().bg().blue().text_str("a").text_str("b");
+        let b = OutputFormatter::new().custom(COLORS::Red, COLORS::Blue).text_str("ab");
+        assert_eq!(a, b);
+        let c = OutputFormatter::new().fg().red().text_str("ab");
+        assert_ne!(a, c);
+        assert_ne!(c, OutputFormatter::new().fg().red().bold().text_str("ab"));
+    }
+
+    #[test]
+    pub fn test_colored() {
+        assert_eq!(
+            OutputFormatter::new().fg().white().bg().red().text_str("warning").print(),
+            colored(COLORS::White, COLORS::Red, "warning")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "log")]
+    pub fn test_log_level_colors() {
+        use logging::{level_color, level_tag};
+        assert_eq!(COLORS::Red, level_color(log::Level::Error));
+        assert_eq!(COLORS::Yellow, level_color(log::Level::Warn));
+        assert_eq!(COLORS::Green, level_color(log::Level::Info));
+        assert_eq!(COLORS::Blue, level_color(log::Level::Debug));
+        assert_eq!(COLORS::Magenta, level_color(log::Level::Trace));
+        assert_eq!("\u{1b}[31mERROR\u{1b}[39;49m", level_tag(log::Level::Error));
+    }
+
+    #[test]
+    #[cfg(feature = "termcolor")]
+    pub fn test_termcolor_writer() {
+        use write_color::ColorWriter;
+        let mut w = ColorWriter::new(termcolor::Buffer::ansi());
+        w.write(&OutputFormatter::new().fg().red().text_str("failed")).unwrap();
+        assert_eq!(b"\x1b[0m\x1b[31mfailed\x1b[0m", w.get_ref().as_slice());
+    }
+
+    #[test]
+    #[cfg(feature = "truecolor")]
+    pub fn test_fg_bg_hex() {
+        assert_eq!(
+            OutputFormatter::new().fg().hex("#fff").bg().hex("#000").text_str("x").print(),
+            OutputFormatter::new().fg_hex("#fff").bg_hex("#000").text_str("x").print()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "truecolor")]
+    pub fn test_render_color_modes() {
+        let c = OutputFormatter::new().fg().hex("#ff8700").bg().hex("#111").text_str("x");
+        assert_eq!(c.print(), c.render(ColorMode::TrueColor));
+        assert_eq!("\u{1b}[38;5;208;48;5;233mx\u{1b}[39;49m", c.render(ColorMode::Ansi256));
+        assert_eq!("\u{1b}[33;40mx\u{1b}[39;49m", c.render(ColorMode::Basic));
+        assert_eq!("x", c.render(ColorMode::None));
+    }
+
+    #[test]
+    pub fn test_detect_color_mode() {
+        use mode::ColorSupport;
+        assert_eq!(ColorMode::None, ColorSupport::detect_from(fake_env(&[("COLORTERM", "truecolor")]), false).mode());
+        assert_eq!(ColorMode::None, ColorSupport::detect_from(fake_env(&[("NO_COLOR", "1")]), true).mode());
+        assert_eq!(
+            ColorMode::TrueColor,
+            ColorSupport::detect_from(fake_env(&[("NO_COLOR", ""), ("COLORTERM", "24bit"), ("TERM", "xterm")]), true).mode()
+        );
+        assert_eq!(ColorMode::Ansi256, ColorSupport::detect_from(fake_env(&[("TERM", "xterm-256color")]), true).mode());
+        assert_eq!(ColorMode::Basic, ColorSupport::detect_from(fake_env(&[("TERM", "xterm")]), true).mode());
+    }
+
+    #[test]
+    pub fn test_trailing_codes_without_text() {
+        assert_eq!("\u{1b}[31m\u{1b}[39;49m", OutputFormatter::new().fg().red().print());
+        assert_eq!(
+            "\u{1b}[31ma\u{1b}[1m\u{1b}[39;49;22m",
+            OutputFormatter::new().fg().red().text_str("a").bold().print()
+        );
+        assert_eq!("\u{1b}[31ma\u{1b}[39;49m", OutputFormatter::new().fg().red().text_str("a").reset().print());
+        assert_eq!("\u{1b}[31m\u{1b}[39;49m", OutputFormatter::new().fg().red().render_minified());
+    }
+
+    #[test]
+    pub fn test_map_text() {
+        let c = OutputFormatter::new()
+            .fg().red().text_str("hello ")
+            .fg().blue().text_str("world")
+            .map_text(|t| t.to_uppercase());
+        assert_eq!("\u{1b}[31mHELLO \u{1b}[34mWORLD\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_rgb_to_ansi88() {
+        assert_eq!(64, Color::rgb_to_ansi88(255, 0, 0));
+        assert_eq!(28, Color::rgb_to_ansi88(0, 255, 0));
+        assert_eq!(81, Color::rgb_to_ansi88(92, 92, 92));
+        assert_eq!(
+            "\u{1b}[38;5;64mx\u{1b}[39;49m",
+            OutputFormatter::from_ansi("\u{1b}[38;5;196mx").render(ColorMode::Ansi88)
+        );
+    }
+
+    #[test]
+    pub fn test_spaces_and_tab_are_uncolored() {
+        let c = OutputFormatter::new().bg().red().text_str("a").spaces(3).tab();
+        let segments: Vec<(String, String)> = c.segments().collect();
+        assert_eq!(("\u{1b}[0m".to_string(), "   ".to_string()), segments[1]);
+        assert_eq!((String::new(), "\t".to_string()), segments[2]);
+        assert_eq!("\u{1b}[41ma\u{1b}[0m   \t\u{1b}[41m\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_current_colors() {
+        let c = OutputFormatter::new().fg().green();
+        assert_eq!(Some(COLORS::Green), c.current_fg());
+        assert_eq!(None, c.current_bg());
+        let c = c.text_str("a").bg().blue().text_str("b").fg().default_color();
+        assert_eq!(Some(COLORS::Default), c.current_fg());
+        assert_eq!(Some(COLORS::Blue), c.current_bg());
+        assert_eq!(None, c.reset().current_fg());
+    }
+
+    #[test]
+    #[cfg(feature = "truecolor")]
+    pub fn test_empty_hex_values() {
+        assert_eq!(Err(HexError::Empty), Color::parse_hex("#"));
+        assert_eq!(Err(HexError::Empty), Color::parse_hex("  "));
+        assert_eq!(Ok(COLORS::Rgb(255, 255, 255)), Color::parse_hex("# fff "));
+        assert_eq!(
+            "\u{1b}[38;2;255;255;255mx\u{1b}[39;49m",
+            OutputFormatter::new().fg().try_hex("# fff ").unwrap().text_str("x").print()
+        );
+    }
+
+    #[test]
+    pub fn test_fg_styled() {
+        assert_eq!(
+            OutputFormatter::new().fg().red().bold().underline().text_str("x").print(),
+            OutputFormatter::new().fg_styled(COLORS::Red, StyleFlags::BOLD | StyleFlags::UNDERLINE).text_str("x").print()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "truecolor")]
+    pub fn test_colors_hex_rgb_equality() {
+        assert_eq!(COLORS::HEX("#ff0000".into()), COLORS::Rgb(255, 0, 0));
+        assert_eq!(COLORS::HEX("#f00".into()), COLORS::HEX("#ff0000".into()));
+        assert_ne!(COLORS::HEX("#ff0000".into()), COLORS::Rgb(255, 0, 1));
+        assert_ne!(COLORS::Red, COLORS::Rgb(128, 0, 0));
+        assert_eq!("HEX(\"#f00\", (255, 0, 0))", format!("{:?}", COLORS::HEX("#f00".into())));
+        assert_eq!("(255, 0, 0)", format!("{}", COLORS::Rgb(255, 0, 0)));
+    }
+
+    #[test]
+    pub fn test_resolve_color() {
+        assert_eq!((Some((0, 128, 0)), Some((192, 192, 192))), Color::new(COLORS::Green, COLORS::White).resolve());
+        assert_eq!((None, None), Color::new(COLORS::None, COLORS::Default).resolve());
+        #[cfg(feature = "truecolor")]
+        assert_eq!((Some((171, 19, 70)), None), Color::new(COLORS::HEX("#ab1346".into()), COLORS::None).resolve());
+    }
+
+    #[test]
+    pub fn test_line_prefix() {
+        let c = OutputFormatter::new()
+            .line_prefix("| ")
+            .fg().red().text_str("first\nsec")
+            .fg().blue().text_str("ond\nthird\n")
+            .text_str("");
+        assert_eq!("| first\n| second\n| third\n", c.plain());
+        assert_eq!("\u{1b}[31m| first\n| sec\u{1b}[34mond\n| third\n\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_strip_ansi() {
+        assert_eq!("red and bold", strip_ansi("\u{1b}[31mred\u{1b}[0m and \u{1b}[1;38;2;1;2;3mbold\u{1b}[22m"));
+        assert_eq!("docs", strip_ansi("\u{1b}]8;;https://docs.rs\u{1b}\\docs\u{1b}]8;;\u{1b}\\"));
+        assert_eq!("no escapes here", strip_ansi("no escapes here"));
+        let c = OutputFormatter::new().fg().red().text_str("a").bold().text_str("b");
+        assert_eq!(c.plain(), strip_ansi(&c.print()));
+    }
+
+    #[test]
+    #[cfg(feature = "truecolor")]
+    pub fn test_color_over_background() {
+        let white = COLORS::Rgb(255, 255, 255);
+        assert_eq!(COLORS::Rgb(255, 128, 128), Color::over(&COLORS::Rgb(255, 0, 0), &white, 0.5));
+        assert_eq!(COLORS::Rgb(255, 0, 0), Color::over(&COLORS::Rgb(255, 0, 0), &white, 2.0));
+        assert_eq!(white, Color::over(&COLORS::Rgb(255, 0, 0), &white, -1.0));
+        assert_eq!(white, Color::over(&COLORS::None, &white, 0.5));
+    }
+
+    #[test]
+    #[cfg(feature = "truecolor")]
+    pub fn test_custom_hex() {
+        assert_eq!(
+            OutputFormatter::new().fg().hex("#fff").bg().hex("#000").text_str("x").print(),
+            OutputFormatter::new().custom_hex("#fff", "#000").text_str("x").print()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "truecolor")]
+    #[should_panic(expected = "Empty Hex Value")]
+    pub fn test_custom_hex_invalid() {
+        OutputFormatter::new().custom_hex("#fff", "#");
+    }
+
+    #[test]
+    pub fn test_framed_and_encircled() {
+        assert_eq!("\u{1b}[51ma\u{1b}[39;49;54m", OutputFormatter::new().framed().text_str("a").print());
+        assert_eq!(
+            "\u{1b}[31;51;52ma\u{1b}[39;49;54m",
+            OutputFormatter::new().fg().red().framed().encircled().text_str("a").print()
+        );
+    }
+
+    #[test]
+    pub fn test_gradient_256() {
+        let c = OutputFormatter::new().gradient_256("abcde", 232, 240);
+        let segments: Vec<(String, String)> = c.segments().collect();
+        assert_eq!(5, segments.len());
+        assert_eq!("\u{1b}[38;5;232m", segments[0].0);
+        assert_eq!("\u{1b}[38;5;236m", segments[2].0);
+        assert_eq!("\u{1b}[38;5;240m", segments[4].0);
+        assert_eq!("\u{1b}[38;5;240mx\u{1b}[39;49m", OutputFormatter::new().gradient_256("x", 240, 232).print());
+        assert_eq!("\u{1b}[39;49m", OutputFormatter::new().gradient_256("", 232, 240).print());
+        assert_eq!(Some(COLORS::Ansi256(208)), OutputFormatter::new().fg_ansi256(208).current_fg());
+    }
+
+    #[test]
+    pub fn test_reset_policy() {
+        let c = OutputFormatter::new().fg().red().bold().text_str("a").bg().blue().text_str("b");
+        assert_eq!("\u{1b}[31;1ma\u{1b}[44mb\u{1b}[39;49;22m", c.clone().reset_policy(RenderReset::Minimal).print());
+        assert_eq!("\u{1b}[0;31;1ma\u{1b}[0;31;44;1mb\u{1b}[0m", c.reset_policy(RenderReset::Full).print());
+    }
+
+    #[test]
+    pub fn test_color_with_styles() {
+        let c = Color::with_styles(COLORS::Green, COLORS::None, &[Style::Bold]);
+        assert_eq!("\u{1b}[32;1mhi\u{1b}[39;49;22m", c.print("hi".to_string()));
+        let c = Color::with_styles(COLORS::None, COLORS::Red, &[Style::Bold, Style::Dim, Style::Underline]);
+        assert_eq!("\u{1b}[41;1;2;4m", c.set());
+        assert_eq!("\u{1b}[39;49;22;24m", c.unset());
+    }
+
+    #[test]
+    pub fn test_text_lines() {
+        let c = OutputFormatter::new().bg().blue().text_lines("first\nsecond\n");
+        let segments: Vec<(String, String)> = c.segments().collect();
+        assert_eq!(
+            vec![
+                ("\u{1b}[44m".to_string(), "first".to_string()),
+                ("\u{1b}[0m".to_string(), "\n".to_string()),
+                ("\u{1b}[44m".to_string(), "second".to_string()),
+                ("\u{1b}[0m".to_string(), "\n".to_string()),
+                ("\u{1b}[44m".to_string(), "".to_string()),
+            ],
+            segments
+        );
+        let c = c.text_str("third");
+        assert_eq!("\u{1b}[44mfirst\u{1b}[0m\n\u{1b}[44msecond\u{1b}[0m\n\u{1b}[44mthird\u{1b}[39;49m", c.print());
+        assert_eq!(OutputFormatter::new().bg().blue(), OutputFormatter::new().bg().blue().text_lines(""));
+    }
+
+    #[test]
+    pub fn test_format_into() {
+        let codes = vec!["38;2;1;2;3".to_string(), "44".to_string(), "1".to_string()];
+        let mut buf = String::new();
+        Color::format_into(&mut buf, &codes);
+        assert_eq!(Color::format(&codes), buf);
+        Color::format_into(&mut buf, &[]);
+        assert_eq!(format!("{}\u{1b}[m", Color::format(&codes)), buf);
+    }
+
+    #[test]
+    pub fn test_dumb_terminal_has_no_colors() {
+        use mode::ColorSupport;
+        let dumb = |name: &str| if name == "TERM" { Some("dumb".to_string()) } else { None };
+        let mode = ColorSupport::detect_from(dumb, true).mode();
+        assert_eq!(ColorMode::None, mode);
+        assert_eq!(ColorMode::None, ColorSupport::detect_from(|_: &str| None, true).mode());
+        let c = OutputFormatter::new().fg().red().text_str("plain");
+        assert_eq!("plain", c.render(mode));
+    }
+
+    #[test]
+    pub fn test_style_off() {
+        let c = OutputFormatter::new().bold().text_str("a").bold_off().text_str("b");
+        assert_eq!("\u{1b}[1ma\u{1b}[22mb\u{1b}[39;49;22m", c.print());
+        let c = OutputFormatter::new().italic().underline().text_str("a").italic_off().text_str("b").underline_off();
+        assert_eq!("\u{1b}[3;4ma\u{1b}[23mb\u{1b}[39;49;23;24m", c.print());
+    }
+
+    #[test]
+    pub fn test_color_support() {
+        use mode::ColorSupport;
+        let support = ColorSupport::detect_from(fake_env(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")]), true);
+        assert!(support.enabled && support.basic && support.ansi256 && support.truecolor);
+        let support = ColorSupport::detect_from(fake_env(&[("TERM", "xterm-256color")]), true);
+        assert!(support.ansi256 && !support.truecolor);
+        assert_eq!(ColorMode::Ansi256, support.mode());
+        let support = ColorSupport::detect_from(fake_env(&[("TERM", "xterm")]), false);
+        assert!(!support.enabled && !support.basic);
+        let support = ColorSupport::detect_from(fake_env(&[("CLICOLOR_FORCE", "1")]), false);
+        assert!(support.enabled && support.basic && !support.ansi256);
+        assert!(!ColorSupport::detect_from(fake_env(&[("CLICOLOR_FORCE", "0")]), false).enabled);
+        assert!(!ColorSupport::detect_from(fake_env(&[("CLICOLOR", "0"), ("TERM", "xterm")]), true).enabled);
+        assert!(!ColorSupport::detect_from(fake_env(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]), true).enabled);
+    }
+
+    #[test]
+    pub fn test_text_display() {
+        let c = OutputFormatter::new().fg().green().text_display(-42).fg().red().text_display(1.5);
+        assert_eq!("\u{1b}[32m-42\u{1b}[31m1.5\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_boxed() {
+        let c = OutputFormatter::new().fg().red().text_str("hi").boxed(COLORS::Blue);
+        assert_eq!("┌────┐\n│ hi │\n└────┘", c.plain());
+        assert_eq!(
+            "\u{1b}[34m┌────┐\n│ \u{1b}[0;31mhi\u{1b}[0m\u{1b}[34m │\n└────┘\u{1b}[39;49m",
+            c.print()
+        );
+        let c = OutputFormatter::new().text_str("a\n").bold().text_str("long\n").boxed(COLORS::None);
+        assert_eq!("┌──────┐\n│ a    │\n│ long │\n└──────┘", c.plain());
+        assert_eq!("┌──┐\n│  │\n└──┘", OutputFormatter::new().boxed(COLORS::Green).plain());
+    }
+
+    #[test]
+    pub fn test_ansi256_fg_and_bg_codes() {
+        assert_eq!(
+            vec!["38;5;5".to_string(), "48;5;9".to_string()],
+            Color::new(COLORS::Ansi256(5), COLORS::Ansi256(9)).apply()
+        );
+        assert_eq!("\u{1b}[38;5;1mx\u{1b}[39;49m", OutputFormatter::new().fg().ansi256(1).text_str("x").print());
+        assert_eq!("\u{1b}[48;5;1mx\u{1b}[39;49m", OutputFormatter::new().bg().ansi256(1).text_str("x").print());
+        let c = OutputFormatter::new().custom(COLORS::Ansi256(255), COLORS::Ansi256(0)).text_str("x");
+        assert_eq!("\u{1b}[38;5;255;48;5;0mx\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_push_segment() {
+        let mut c = OutputFormatter::new();
+        c.push_segment(COLORS::Red, COLORS::None, &[], "a");
+        c.push_segment(COLORS::None, COLORS::Blue, &[Style::Bold, Style::Italic], "b");
+        let chain = OutputFormatter::new()
+            .fg().red().text_str("a")
+            .bg().blue().bold().italic().text_str("b");
+        assert_eq!(format!("{:?}", chain), format!("{:?}", c));
+        assert_eq!(chain.print(), c.print());
+    }
+
+    #[test]
+    pub fn test_progress_bar() {
+        let count = |c: &OutputFormatter, cell: char| c.plain().chars().filter(|&ch| ch == cell).count();
+        let empty = OutputFormatter::new().progress_bar(0.0, 8, COLORS::Green, COLORS::White);
+        assert_eq!((0, 8), (count(&empty, '█'), count(&empty, '░')));
+        let half = OutputFormatter::new().progress_bar(0.5, 8, COLORS::Green, COLORS::White);
+        assert_eq!((4, 4), (count(&half, '█'), count(&half, '░')));
+        assert_eq!("\u{1b}[32m████\u{1b}[37m░░░░\u{1b}[39;49m", half.print());
+        let full = OutputFormatter::new().progress_bar(1.5, 8, COLORS::Green, COLORS::White);
+        assert_eq!((8, 0), (count(&full, '█'), count(&full, '░')));
+    }
+
+    #[test]
+    pub fn test_save_and_restore_style() {
+        let c = OutputFormatter::new()
+            .fg().red().bold().text_str("a")
+            .save_style()
+            .fg().green().text_str("b")
+            .save_style()
+            .underline().text_str("c")
+            .restore_style()
+            .text_str("d")
+            .restore_style()
+            .text_str("e");
+        assert_eq!(
+            "\u{1b}[31;1ma\u{1b}[32mb\u{1b}[4mc\u{1b}[0;32;1md\u{1b}[0;31;1me\u{1b}[39;49;22;24m",
+            c.print()
+        );
+        assert_eq!(None, OutputFormatter::new().fg().red().restore_style().current_fg());
+    }
+
+    #[test]
+    pub fn test_ansi16_name() {
+        assert_eq!(Some("magenta"), COLORS::Magenta.ansi16_name());
+        assert_eq!(Some("default"), COLORS::Default.ansi16_name());
+        assert_eq!(None, COLORS::None.ansi16_name());
+        #[cfg(feature = "truecolor")]
+        assert_eq!(None, COLORS::HEX("#ff0000".into()).ansi16_name());
+        assert_eq!(Some("cyan"), Color::new(COLORS::Cyan, COLORS::White).ansi16_name());
+        assert_eq!(None, Color::new(COLORS::None, COLORS::White).ansi16_name());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    pub fn test_write_colored_macro() {
+        let mut out: Vec<u8> = vec![];
+        write_colored!(out, red bold "error: ", "details").unwrap();
+        let expected = OutputFormatter::new().fg().red().bold().text_str("error: ").reset().text_str("details");
+        assert_eq!(expected.to_bytes(), out);
+    }
+
+    #[test]
+    #[cfg(feature = "truecolor")]
+    pub fn test_hex_case_and_whitespace() {
+        let expected = Ok(COLORS::Rgb(255, 170, 187));
+        assert_eq!(expected, Color::parse_hex("#ffaabb"));
+        assert_eq!(expected, Color::parse_hex("#FFAABB"));
+        assert_eq!(expected, Color::parse_hex("#FfAabb"));
+        assert_eq!(expected, Color::parse_hex(" #FfAabb "));
+        assert_eq!(Err(HexError::InvalidDigit('g')), Color::parse_hex("#ffaabg"));
+        assert_eq!(Err(HexError::InvalidDigit('#')), Color::parse_hex("#ff#abb"));
+        assert_eq!(Err(HexError::InvalidDigit(' ')), Color::parse_hex("#ff aabb"));
+        assert!(OutputFormatter::new().fg().try_hex("#ffaazz").is_err());
+    }
+
+    #[test]
+    pub fn test_repeat_builder() {
+        let c = OutputFormatter::new().fg().red().text_str("a").fg().blue().text_str("b").repeat_builder(3);
+        let texts = c.entries().filter(|e| matches!(e, Entry::Text(_))).count();
+        assert_eq!(6, texts);
+        assert_eq!("ababab", c.plain());
+        assert_eq!(
+            "\u{1b}[31ma\u{1b}[34mb\u{1b}[31ma\u{1b}[34mb\u{1b}[31ma\u{1b}[34mb\u{1b}[39;49m",
+            c.print()
+        );
+        assert_eq!(OutputFormatter::new(), OutputFormatter::new().fg().red().text_str("a").repeat_builder(0));
+    }
+
+    #[test]
+    #[cfg(feature = "truecolor")]
+    pub fn test_from_rgb_u32() {
+        assert_eq!(COLORS::Rgb(255, 128, 0), Color::from_rgb_u32(0xFF8000));
+        assert_eq!(COLORS::Rgb(0, 0, 1), Color::from_rgb_u32(0xFF00_0001));
+        assert_eq!(
+            OutputFormatter::new().fg().rgb(255, 128, 0).text_str("x").print(),
+            OutputFormatter::new().fg().rgb_u32(0xFF8000).text_str("x").print()
+        );
+    }
+
+    #[test]
+    pub fn test_render_none_keep_links() {
+        let c = OutputFormatter::new()
+            .fg().blue().underline()
+            .link("https://docs.rs")
+            .text_str("docs")
+            .reset()
+            .text_str(" end");
+        assert_eq!(
+            "\u{1b}]8;;https://docs.rs\u{1b}\\docs\u{1b}]8;;\u{1b}\\ end",
+            c.render(ColorMode::NoneKeepLinks)
+        );
+        assert_eq!("docs end", c.render(ColorMode::None));
+    }
+
+    #[test]
+    pub fn test_clicolor_precedence() {
+        use mode::ColorSupport;
+        let mode = |vars, is_tty| ColorSupport::detect_from(fake_env(vars), is_tty).mode();
+        // CLICOLOR=0 disables colors on a terminal, any other value keeps them
+        assert_eq!(ColorMode::None, mode(&[("CLICOLOR", "0"), ("TERM", "xterm")], true));
+        assert_eq!(ColorMode::Basic, mode(&[("CLICOLOR", "1"), ("TERM", "xterm")], true));
+        // CLICOLOR_FORCE wins over the terminal check and over CLICOLOR
+        assert_eq!(ColorMode::Ansi256, mode(&[("CLICOLOR_FORCE", "1"), ("TERM", "xterm-256color")], false));
+        assert_eq!(ColorMode::Basic, mode(&[("CLICOLOR_FORCE", "1"), ("CLICOLOR", "0"), ("TERM", "xterm")], true));
+        assert_eq!(ColorMode::None, mode(&[("CLICOLOR_FORCE", ""), ("TERM", "xterm")], false));
+        // NO_COLOR wins over both
+        assert_eq!(ColorMode::None, mode(&[("NO_COLOR", "1"), ("CLICOLOR", "1")], true));
+        assert_eq!(ColorMode::None, mode(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1"), ("CLICOLOR", "1")], false));
+    }
+
+    #[test]
+    pub fn test_base_colors_keep_palette_index() {
+        let base = [
+            COLORS::Black,
+            COLORS::Red,
+            COLORS::Green,
+            COLORS::Yellow,
+            COLORS::Blue,
+            COLORS::Magenta,
+            COLORS::Cyan,
+            COLORS::White,
+        ];
+        for (i, color) in base.iter().enumerate() {
+            assert_eq!(Some(i as u8), Color::to_ansi256(color));
+        }
+        assert_eq!(None, Color::to_ansi256(&COLORS::Default));
+        let c = OutputFormatter::new().fg().red().text_str("x");
+        assert_eq!("\u{1b}[31mx\u{1b}[39;49m", c.render(ColorMode::Ansi256));
+        let c = OutputFormatter::new().fg().ansi256(1).bg().ansi256(12).text_str("x");
+        assert_eq!("\u{1b}[31;44mx\u{1b}[39;49m", c.render(ColorMode::Basic));
+        assert_eq!("\u{1b}[38;5;1;48;5;12mx\u{1b}[39;49m", c.render(ColorMode::Ansi88));
+    }
+
+    #[test]
+    pub fn test_redact() {
+        let c = OutputFormatter::new().fg().red().bold().text_str("abcd").redact('*');
+        assert_eq!("\u{1b}[31;1m****\u{1b}[39;49;22m", c.print());
+        let c = OutputFormatter::new().fg().green().text_str("a b\tc").redact('#');
+        assert_eq!("# #\t#", c.plain());
+    }
+
+    #[test]
+    pub fn test_wrap() {
+        let c = OutputFormatter::new()
+            .fg().red().text_str("the quick brown fox ")
+            .fg().green().text_str("jumps over a extraordinarily lazy dog")
+            .wrap(10);
+        let plain = c.plain();
+        let lines: Vec<&str> = plain.split('\n').collect();
+        assert_eq!(
+            vec!["the quick", "brown fox", "jumps over", "a", "extraordin", "arily lazy", "dog"],
+            lines
+        );
+        assert!(lines.iter().all(|line| line.chars().count() <= 10));
+        let c = OutputFormatter::new().bg().blue().text_str("aa bb").wrap(2);
+        assert_eq!("\u{1b}[44maa\u{1b}[0m\n\u{1b}[44mbb\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    #[cfg(feature = "truecolor")]
+    pub fn test_invert_rgb() {
+        assert_eq!(COLORS::Rgb(255, 255, 255), Color::invert_rgb(&COLORS::Black));
+        assert_eq!(COLORS::Rgb(245, 235, 225), Color::invert_rgb(&COLORS::Rgb(10, 20, 30)));
+        assert_eq!(COLORS::Default, Color::invert_rgb(&COLORS::Default));
+        let c = OutputFormatter::new().fg().black().bg().default_color().text_str("x").negative();
+        assert_eq!("\u{1b}[38;2;255;255;255;49mx\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_highlight() {
+        let c = OutputFormatter::new().highlight("hello world", "lo", COLORS::Red, COLORS::White, true);
+        assert_eq!("\u{1b}[37mhel\u{1b}[31mlo\u{1b}[37m world\u{1b}[39;49m", c.print());
+        let c = OutputFormatter::new().highlight("hello world", "LO", COLORS::Red, COLORS::White, true);
+        assert_eq!("\u{1b}[37mhello world\u{1b}[39;49m", c.print());
+        let c = OutputFormatter::new().highlight("hello world", "LO", COLORS::Red, COLORS::White, false);
+        assert_eq!("\u{1b}[37mhel\u{1b}[31mlo\u{1b}[37m world\u{1b}[39;49m", c.print());
+        // occurrences do not overlap
+        let c = OutputFormatter::new().highlight("aaa", "aa", COLORS::Red, COLORS::White, true);
+        assert_eq!("\u{1b}[31maa\u{1b}[37ma\u{1b}[39;49m", c.print());
+        let c = OutputFormatter::new().highlight("hello", "", COLORS::Red, COLORS::White, true);
+        assert_eq!("\u{1b}[37mhello\u{1b}[39;49m", c.print());
+        assert_eq!("hello", c.plain());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    pub fn test_io_write() {
+        use std::io::Write;
+        let mut c = OutputFormatter::new().fg().green();
+        c.write_all(b"ok ").unwrap();
+        write!(c, "{} files", 3).unwrap();
+        c.write_all(&[b'a', 0xff, b'b']).unwrap();
+        assert_eq!("\u{1b}[32mok 3 filesa\u{fffd}b\u{1b}[39;49m", c.print());
+        assert_eq!(0, c.write(b"").unwrap());
+    }
+
+    #[test]
+    pub fn test_parse_sgr() {
+        #[cfg(feature = "truecolor")]
+        assert_eq!((COLORS::Rgb(255, 0, 0), COLORS::None, vec![]), Color::parse_sgr(&[38, 2, 255, 0, 0]));
+        #[cfg(not(feature = "truecolor"))]
+        assert_eq!((COLORS::Ansi256(196), COLORS::None, vec![]), Color::parse_sgr(&[38, 2, 255, 0, 0]));
+        assert_eq!((COLORS::Red, COLORS::None, vec![Style::Bold]), Color::parse_sgr(&[1, 31]));
+        assert_eq!((COLORS::None, COLORS::None, vec![]), Color::parse_sgr(&[0]));
+        assert_eq!((COLORS::None, COLORS::Blue, vec![]), Color::parse_sgr(&[1, 31, 0, 44]));
+        assert_eq!((COLORS::Ansi256(9), COLORS::Default, vec![]), Color::parse_sgr(&[3, 91, 49, 23]));
+        assert_eq!(
+            (COLORS::Green, COLORS::Ansi256(208), vec![Style::Underline]),
+            Color::parse_sgr(&[48, 5, 208, 4, 32])
+        );
+        // parameters that are cut off or out of range set nothing
+        assert_eq!((COLORS::None, COLORS::None, vec![]), Color::parse_sgr(&[38, 5]));
+        assert_eq!((COLORS::None, COLORS::None, vec![]), Color::parse_sgr(&[38, 5, 300]));
+    }
+
+    #[test]
+    pub fn test_render_without_trailing_reset() {
+        let c = OutputFormatter::new().fg().red().bold().text_str("a").bg().blue().text_str("b");
+        let rendered = c.render_without_trailing_reset();
+        assert!(rendered.ends_with('b'));
+        assert_eq!(c.print(), rendered + &c.final_reset());
+    }
+
+    #[test]
+    pub fn test_color_resets() {
+        assert_eq!("\u{1b}[39m", Color::reset_fg());
+        assert_eq!("\u{1b}[49m", Color::reset_bg());
+        assert_eq!("\u{1b}[0m", Color::reset_all());
+        let c = Color::new(COLORS::Red, COLORS::Blue);
+        assert_eq!("\u{1b}[39;49m", c.unset());
+    }
+
+    #[test]
+    pub fn test_fg_from_hash() {
+        let color = |key: &str| OutputFormatter::new().fg_from_hash(key).current_fg();
+        assert_eq!(color("alice"), color("alice"));
+        assert_ne!(color("alice"), color("bob"));
+        // the hash must not change between versions, or colors would change for users
+        assert_eq!(Some(COLORS::Ansi256(207)), color("alice"));
+        let palette = [COLORS::Red, COLORS::Green, COLORS::Blue];
+        let color = |key: &str| OutputFormatter::new().fg_from_hash_in(key, &palette).current_fg();
+        assert_eq!(color("thread-1"), color("thread-1"));
+        assert!(palette.iter().any(|c| Some(c.clone()) == color("thread-1")));
+        assert_eq!(None, OutputFormatter::new().fg_from_hash_in("thread-1", &[]).current_fg());
+    }
+
+    #[test]
+    pub fn test_html_transparent_background() {
+        use crate::render::HtmlRenderer;
+        let c = OutputFormatter::new()
+            .fg().white().bg().transparent().text_str("clear")
+            .bg().blue().text_str("blue")
+            .bg().default_color().text_str("inherited");
+        assert_eq!(
+            "<span style=\"color: #c0c0c0; background: transparent\">clear</span>\
+             <span style=\"color: #c0c0c0; background: #000080\">blue</span>\
+             <span style=\"color: #c0c0c0\">inherited</span>",
+            c.render_with(&HtmlRenderer)
+        );
+        // the terminal has no transparency, so nothing is emitted for it
+        assert_eq!(
+            OutputFormatter::new().fg().white().text_str("x").print(),
+            OutputFormatter::new().fg().white().bg().transparent().text_str("x").print()
+        );
+        let c = OutputFormatter::new().link("https://a.b?c=1&d=2").underline().text_str("<a>").reset().text_str("b");
+        assert_eq!(
+            "<a href=\"https://a.b?c=1&amp;d=2\"><span style=\"text-decoration: underline\">&lt;a&gt;</span></a>b",
+            c.render_with(&HtmlRenderer)
+        );
+    }
+
+    #[test]
+    pub fn test_single_segment_fast_path() {
+        let general = |c: &OutputFormatter| c.render_without_trailing_reset() + &c.final_reset();
+        let builders = [
+            OutputFormatter::new().text_str("plain"),
+            OutputFormatter::new().fg().red().text_str("red"),
+            OutputFormatter::new().fg().red().bg().blue().bold().underline().text_str("styled"),
+            OutputFormatter::new().fg().red().text_str(""),
+            OutputFormatter::new().custom(COLORS::Default, COLORS::None).text_str("x"),
+        ];
+        for c in builders.iter() {
+            assert!(c.print_single_segment().is_some());
+            assert_eq!(general(c), c.print());
+        }
+        // everything else takes the general path
+        assert!(OutputFormatter::new().fg().red().text_str("a").text_str("b").print_single_segment().is_none());
+        assert!(OutputFormatter::new().fg().red().text_str("a").bold().print_single_segment().is_none());
+        assert!(OutputFormatter::new().line_prefix("> ").text_str("a").print_single_segment().is_none());
+    }
+
+    #[test]
+    pub fn test_concat_str() {
+        let mut c = OutputFormatter::new().bg().red().text_str("a");
+        c.concat_str("plain");
+        let rendered = c.print();
+        assert!(rendered.starts_with("\u{1b}[41ma\u{1b}[0mplain"));
+        assert_eq!("aplain", c.plain());
+        // codes waiting for a text do not color the plain text either
+        let mut c = OutputFormatter::new().bg().red();
+        c.concat_str("plain");
+        assert!(c.print().starts_with("\u{1b}[0mplain"));
+    }
+
+    #[test]
+    pub fn test_line_ending() {
+        let c = OutputFormatter::new().line_ending(LineEnding::Crlf).bg().red().text_lines("a\nb");
+        assert_eq!("\u{1b}[41ma\u{1b}[0m\r\n\u{1b}[41mb\u{1b}[39;49m", c.print());
+        assert_eq!("a\r\nb", c.plain());
+        let c = OutputFormatter::new().line_ending(LineEnding::Crlf).line_prefix("> ").text_str("a\r\nb\n");
+        assert_eq!("> a\r\n> b\r\n", c.plain());
+        let c = OutputFormatter::new().line_ending(LineEnding::Crlf).fg().red().text_str("one two").wrap(3);
+        assert_eq!("one\r\ntwo", c.plain());
+        assert_eq!("a\nb", OutputFormatter::new().text_str("a\nb").plain());
+        assert_ne!(OutputFormatter::new(), OutputFormatter::new().line_ending(LineEnding::Crlf));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    pub fn test_color_distance() {
+        let navy = COLORS::Ansi256(18);
+        assert!(Color::distance(&navy, &COLORS::Blue) < Color::distance(&navy, &COLORS::Cyan));
+        assert!(Color::distance(&COLORS::Green, &COLORS::Cyan) < Color::distance(&COLORS::Green, &COLORS::Magenta));
+        assert!(Color::distance(&COLORS::Black, &COLORS::Ansi256(232)) < Color::distance(&COLORS::Black, &COLORS::Blue));
+        assert_eq!(Color::distance(&COLORS::Red, &COLORS::Blue), Color::distance(&COLORS::Blue, &COLORS::Red));
+        assert_eq!(0.0, Color::distance(&COLORS::Ansi256(1), &COLORS::Red));
+        assert_eq!(f32::INFINITY, Color::distance(&COLORS::Red, &COLORS::Default));
+        // a color closer to the gray ramp than to the color cube maps to the ramp
+        assert_eq!(233, Color::rgb_to_ansi256(20, 18, 16));
+        assert_eq!(16, Color::rgb_to_ansi256(0, 0, 0));
+    }
+
+    #[test]
+    pub fn test_clear_keep_style() {
+        let mut c = OutputFormatter::new().fg().red().bg().blue().bold().text_str("first");
+        c.clear_keep_style();
+        assert_eq!("", c.plain());
+        let c = c.text_str("second");
+        assert_eq!("\u{1b}[31;44;1msecond\u{1b}[39;49;22m", c.print());
+        assert_eq!(Some(COLORS::Red), c.current_fg());
+        let mut c = OutputFormatter::new().line_prefix("> ").fg().red().text_str("a").reset().text_str("b");
+        c.clear_keep_style();
+        assert_eq!(OutputFormatter::new().line_prefix("> "), c);
+        let mut c = c.fg().red().text_str("a");
+        c.clear();
+        assert_eq!(None, c.text_str("b").current_fg());
+    }
+
+    #[test]
+    pub fn test_color_to_css() {
+        assert_eq!((Some("#008000".to_string()), None), Color::new(COLORS::Green, COLORS::None).to_css());
+        assert_eq!((None, None), Color::new(COLORS::None, COLORS::Default).to_css());
+        assert_eq!((None, Some("transparent".to_string())), Color::new(COLORS::None, COLORS::Transparent).to_css());
+        assert_eq!((Some("#ff8700".to_string()), None), Color::new(COLORS::Ansi256(208), COLORS::None).to_css());
+        #[cfg(feature = "truecolor")]
+        {
+            let c = Color::new(COLORS::HEX("#AB1346".to_string()), COLORS::HEX("#fff".to_string()));
+            assert_eq!((Some("#ab1346".to_string()), Some("#ffffff".to_string())), c.to_css());
+            assert_eq!((Some("#0a141e".to_string()), None), Color::new(COLORS::Rgb(10, 20, 30), COLORS::None).to_css());
+        }
+    }
+
+    #[test]
+    pub fn test_indent() {
+        let c = OutputFormatter::new().indent(4).bg().red().text_lines("first\nsecond");
+        let rendered = c.print();
+        for line in rendered.split('\n') {
+            assert!(strip_ansi(line).starts_with("    "));
+            // the background is switched off before the indent, the first line has nothing to switch off
+            assert!(line.starts_with("    \u{1b}[41m") || line.starts_with("\u{1b}[0m    \u{1b}[41m"));
+        }
+        assert_eq!("    \u{1b}[41mfirst\u{1b}[0m\n    \u{1b}[41msecond\u{1b}[39;49m", rendered);
+        assert_eq!("    first\n    second", c.plain());
+        let c = OutputFormatter::new().indent(2).line_prefix("> ").text_str("a\nb\n");
+        assert_eq!("  > a\n  > b\n", c.plain());
+        assert_eq!("  > a\n  > b\n\u{1b}[39;49m", c.print());
+    }
+
+    #[test]
+    pub fn test_style_sgr_table() {
+        let expected: [(Style, u16, u16); 13] = [
+            (Style::Bold, 1, 22),
+            (Style::Dim, 2, 22),
+            (Style::Italic, 3, 23),
+            (Style::Underline, 4, 24),
+            (Style::Blink, 5, 25),
+            (Style::BlinkRapid, 6, 25),
+            (Style::Reverse, 7, 27),
+            (Style::Hidden, 8, 28),
+            (Style::Strikethrough, 9, 29),
+            (Style::DoubleUnderline, 21, 24),
+            (Style::Overline, 53, 55),
+            (Style::Framed, 51, 54),
+            (Style::Encircled, 52, 54),
+        ];
+        for (style, (expected_style, on, off)) in Style::ALL.iter().zip(expected.iter()) {
+            assert_eq!(expected_style, style);
+            assert_eq!((*on, *off), (style.sgr_on(), style.sgr_off()));
+            assert_eq!(Some(*style), Style::from_on_code(&on.to_string()));
+            // every style is switched off by the final reset
+            let c = OutputFormatter::new().style(style.flag()).text_str("x");
+            assert!(c.final_reset().contains(&off.to_string()));
+        }
+        assert_eq!(vec!["22".to_string(), "24".to_string()], Style::off_codes(&[Style::Bold, Style::Dim, Style::Underline]));
+    }
+
+    #[test]
+    pub fn test_colorize_numbers() {
+        let c = OutputFormatter::new().colorize_numbers("took 1500ms at 99.9%", COLORS::Yellow, COLORS::White, true);
+        assert_eq!(
+            "\u{1b}[37mtook \u{1b}[33m1500\u{1b}[37mms at \u{1b}[33m99.9\u{1b}[37m%\u{1b}[39;49m",
+            c.print()
+        );
+        let c = OutputFormatter::new().colorize_numbers("99.9", COLORS::Yellow, COLORS::White, false);
+        assert_eq!("\u{1b}[33m99\u{1b}[37m.\u{1b}[33m9\u{1b}[39;49m", c.print());
+        let c = OutputFormatter::new().colorize_numbers("-5 x-1 1.2.3", COLORS::Yellow, COLORS::White, true);
+        assert_eq!(
+            "\u{1b}[33m-5\u{1b}[37m x-\u{1b}[33m1\u{1b}[37m \u{1b}[33m1.2\u{1b}[37m.\u{1b}[33m3\u{1b}[39;49m",
+            c.print()
+        );
+        assert_eq!("no numbers", OutputFormatter::new().colorize_numbers("no numbers", COLORS::Red, COLORS::None, true).plain());
+    }
+
+    #[test]
+    pub fn test_apply_color() {
+        let color = Color::new(COLORS::Green, COLORS::White);
+        let c = OutputFormatter::new().apply_color(&color).text_str("Hi");
+        assert_eq!(OutputFormatter::new().custom(COLORS::Green, COLORS::White).text_str("Hi").print(), c.print());
+        assert_eq!("\u{1b}[32;47mHi\u{1b}[39;49m", c.print());
+        let styled = Color::with_styles(COLORS::Red, COLORS::None, &[Style::Bold]);
+        let c = OutputFormatter::new().apply_color(&styled).text_str("x");
+        assert_eq!(OutputFormatter::new().custom(COLORS::Red, COLORS::None).bold().text_str("x").print(), c.print());
+    }
+
+    #[test]
+    pub fn test_truncate_middle() {
+        let name = || OutputFormatter::new().fg().blue().text_str("very_long_file").fg().red().text_str("_name.rs");
+        let c = name().truncate_middle(12, "...");
+        assert_eq!("very_...e.rs", c.plain());
+        assert_eq!("\u{1b}[34mvery_...\u{1b}[31me.rs\u{1b}[39;49m", c.print());
+        assert_eq!("very_long_file_name.rs", name().truncate_middle(22, "...").plain());
+        assert_eq!("v...", name().truncate_middle(4, "...").plain());
+        assert_eq!("..", name().truncate_middle(2, "...").plain());
+        assert_eq!("", name().truncate_middle(0, "...").plain());
+        // the prefix and the indent are not part of the text
+        let c = OutputFormatter::new().line_prefix("> ").indent(2).text_str("abc").truncate_middle(4, "…");
+        assert_eq!("  > abc", c.plain());
+        let c = OutputFormatter::new().line_prefix("> ").text_str("abcdef").truncate_middle(4, "…");
+        assert_eq!("> ab…f", c.plain());
+    }
+
+    #[cfg(all(windows, feature = "winconsole"))]
+    #[test]
+    pub fn test_winconsole_attribute() {
+        use crate::winconsole::to_attribute;
+        assert_eq!(0x04, to_attribute(&COLORS::Red, &COLORS::Black, 0x07));
+        assert_eq!(0x12, to_attribute(&COLORS::Green, &COLORS::Blue, 0x07));
+        assert_eq!(0x0e, to_attribute(&COLORS::Ansi256(11), &COLORS::None, 0x07));
+        assert_eq!(0x0c, to_attribute(&COLORS::Ansi256(196), &COLORS::Default, 0x07));
+        assert_eq!(0x76, to_attribute(&COLORS::Yellow, &COLORS::Default, 0x70));
+    }
+
+    #[cfg(feature = "truecolor")]
+    #[test]
+    pub fn test_fg_rgb_if_supported() {
+        let build = |support: ColorSupport| OutputFormatter::new().fg_rgb_if_supported(255, 0, 0, &support).text_str("x").print();
+        assert_eq!("\u{1b}[38;2;255;0;0mx\u{1b}[39;49m", build(fake_support(true, true, true)));
+        assert_eq!("\u{1b}[38;5;196mx\u{1b}[39;49m", build(fake_support(false, true, true)));
+        assert_eq!("\u{1b}[31mx\u{1b}[39;49m", build(fake_support(false, false, true)));
+        assert_eq!("x\u{1b}[39;49m", build(fake_support(false, false, false)));
+    }
+
+    #[test]
+    pub fn test_to_segments() {
+        let c = OutputFormatter::new()
+            .fg().red().bold().text_str("error")
+            .fg().default_color().bg().blue().bold_off().underline().text_str(" details");
+        assert_eq!(
+            vec![
+                RenderedSegment {
+                    fg: Some(COLORS::Red),
+                    bg: None,
+                    styles: vec![Style::Bold],
+                    link: None,
+                    text: "error".to_string(),
+                },
+                RenderedSegment {
+                    fg: Some(COLORS::Default),
+                    bg: Some(COLORS::Blue),
+                    styles: vec![Style::Underline],
+                    link: None,
+                    text: " details".to_string(),
+                },
+            ],
+            c.to_segments()
+        );
+        assert!(OutputFormatter::new().fg().red().to_segments().is_empty());
+    }
+
+    #[test]
+    pub fn test_fg_before_bg_in_any_order() {
+        let fg_first = OutputFormatter::new().fg().red().bg().blue().bold().text_str("x");
+        let bg_first = OutputFormatter::new().bg().blue().fg().red().bold().text_str("x");
+        assert_eq!("\u{1b}[31;44;1mx\u{1b}[39;49;22m", bg_first.print());
+        assert_eq!(fg_first.print(), bg_first.print());
+        assert_eq!(fg_first.render_minified(), bg_first.render_minified());
+        assert_eq!(fg_first.render(ColorMode::Ansi256), bg_first.render(ColorMode::Ansi256));
+        let styled_between = OutputFormatter::new().text_str("a").bg().blue().bold().fg().red().text_str("b");
+        assert_eq!("a\u{1b}[31;1;44mb\u{1b}[39;49;22m", styled_between.print());
+        // a reset keeps the codes before it in place
+        let reset = OutputFormatter::new().bg().blue().reset().fg().red().text_str("x");
+        assert_eq!("\u{1b}[44;0;31mx\u{1b}[39;49m", reset.print());
+    }
+
+    #[test]
+    pub fn test_text_char() {
+        let chars = OutputFormatter::new()
+            .fg().red().text_char('a')
+            .fg().green().text_char('b')
+            .fg().blue().text_char('ü');
+        let strs = OutputFormatter::new()
+            .fg().red().text_str("a")
+            .fg().green().text_str("b")
+            .fg().blue().text_str("ü");
+        assert_eq!(strs.print(), chars.print());
+        assert_eq!(strs, chars);
+    }
+
+    #[test]
+    #[cfg(feature = "log")]
+    pub fn test_log_theme_styled_level() {
+        use logging::LogTheme;
+        let theme = LogTheme::default();
+        let error = |support| theme.styled_level(log::Level::Error, &support).print();
+        #[cfg(feature = "truecolor")]
+        assert_eq!("\u{1b}[38;2;215;0;0;1mERROR\u{1b}[39;49;22m", error(fake_support(true, true, true)));
+        assert_eq!("\u{1b}[38;5;160;1mERROR\u{1b}[39;49;22m", error(fake_support(false, true, true)));
+        assert_eq!("\u{1b}[31;1mERROR\u{1b}[39;49;22m", error(fake_support(false, false, true)));
+        assert_eq!("ERROR", theme.styled_level(log::Level::Error, &fake_support(false, false, false)).plain());
+        let trace = theme.styled_level(log::Level::Trace, &fake_support(false, true, true));
+        assert_eq!("\u{1b}[38;5;244;2mTRACE\u{1b}[39;49;22m", trace.print());
+    }
+
+    #[test]
+    pub fn test_from_ansi_bright_colors() {
+        let c = OutputFormatter::from_ansi("\x1b[91;104mx\x1b[0m");
+        assert_eq!("\u{1b}[38;5;9;48;5;12mx\u{1b}[39;49m", c.print());
+        assert_eq!(Some(COLORS::Ansi256(9)), c.to_segments()[0].fg);
+        assert_eq!(Some(COLORS::Ansi256(12)), c.to_segments()[0].bg);
+        assert_eq!(
+            "<span style=\"color: #ff0000; background: #0000ff\">x</span>",
+            c.render_with(&render::HtmlRenderer)
+        );
+        #[cfg(feature = "truecolor")]
+        assert_eq!(
+            "\u{1b}[38;2;54;54;54;48;2;18;18;18mx\u{1b}[39;49m",
+            c.grayscale().print()
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "log", feature = "truecolor"))]
+    pub fn test_log_theme_from_palette() {
+        use crate::palette::Palette;
+        use logging::LogTheme;
+        let palette = Palette::dracula();
+        let theme = LogTheme::from(&palette);
+        assert_eq!(Some(theme.error.rgb), Color::to_rgb(&palette.error()));
+        assert_eq!(Some(theme.warn.rgb), Color::to_rgb(&palette.warning()));
+        assert_eq!(Some(theme.info.rgb), Color::to_rgb(&palette.success()));
+        assert_eq!(Some(theme.debug.rgb), Color::to_rgb(&palette.accent()));
+        assert_eq!(Some(theme.trace.rgb), Color::to_rgb(&palette.foreground()));
+        assert_eq!(StyleFlags::BOLD, theme.error.flags);
+        assert_eq!(Color::rgb_to_ansi256(0xff, 0x55, 0x55), theme.error.ansi256);
+        assert_eq!(Color::rgb_to_basic(0xff, 0x55, 0x55), theme.error.basic);
+        assert_eq!(
+            "\u{1b}[38;2;255;85;85;1mERROR\u{1b}[39;49;22m",
+            theme.styled_level(log::Level::Error, &fake_support(true, true, true)).print()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    pub fn test_write_to_streams_segments() {
+        // a writer that takes a single write, the segments after it never have to be rendered
+        struct Once(Vec<u8>);
+        impl std::io::Write for Once {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if !self.0.is_empty() {
+                    return Err(std::io::ErrorKind::WriteZero.into());
+                }
+                self.0.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let c = OutputFormatter::new().fg().red().text_str("a").fg().blue().text_str("b");
+        let mut out = Once(vec![]);
+        assert!(c.write_to(&mut out).is_err());
+        assert_eq!(b"\x1b[31m".to_vec(), out.0);
+        let c = c.line_prefix("> ");
+        let mut out: Vec<u8> = vec![];
+        c.write_to(&mut out).unwrap();
+        assert_eq!(c.print(), String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    pub fn test_wrap_word_across_segments() {
+        let c = OutputFormatter::new().fg().red().text_str("a foo").fg().blue().text_str("bar").wrap(7);
+        assert_eq!("a\nfoobar", c.plain());
+        assert_eq!("\u{1b}[31ma\u{1b}[0m\n\u{1b}[31mfoo\u{1b}[34mbar\u{1b}[39;49m", c.print());
+        let c = OutputFormatter::new().fg().red().text_str("foo").fg().blue().text_str("bar").wrap(7);
+        assert_eq!("foobar", c.plain());
+        let c = OutputFormatter::new().fg().red().text_str("ab ").fg().blue().text_str(" cd").wrap(3);
+        assert_eq!("ab\ncd", c.plain());
+    }
 }
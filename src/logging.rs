@@ -0,0 +1,165 @@
+use crate::color::{Color, COLORS};
+use crate::mode::{ColorMode, ColorSupport};
+#[cfg(feature = "truecolor")]
+use crate::palette::Palette;
+use crate::style::StyleFlags;
+use crate::OutputFormatter;
+use alloc::string::String;
+use log::Level;
+
+/// the color a log level is printed in
+///
+/// Example
+/// ```
+/// use terminal_color_builder::color::COLORS;
+/// use terminal_color_builder::logging::level_color;
+/// assert_eq!(COLORS::Red, level_color(log::Level::Error));
+/// ```
+pub fn level_color(level: Level) -> COLORS {
+    match level {
+        Level::Error => COLORS::Red,
+        Level::Warn => COLORS::Yellow,
+        Level::Info => COLORS::Green,
+        Level::Debug => COLORS::Blue,
+        Level::Trace => COLORS::Magenta,
+    }
+}
+
+/// the name of the log level (`ERROR`, `WARN`, ...) in its color
+///
+/// Example
+/// ```
+/// use terminal_color_builder::logging::level_tag;
+/// assert_eq!("\u{1b}[33mWARN\u{1b}[39;49m", level_tag(log::Level::Warn));
+/// ```
+pub fn level_tag(level: Level) -> String {
+    OutputFormatter::new()
+        .custom(level_color(level), COLORS::None)
+        .text_str(level.as_str())
+        .print()
+}
+
+/// How a log level is printed, with a color for each kind of terminal
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelStyle {
+    /// the 24-bit color
+    pub rgb: (u8, u8, u8),
+    /// the entry of the 256-color palette used without 24-bit colors
+    pub ansi256: u8,
+    /// the base color used on terminals with only eight colors
+    pub basic: COLORS,
+    pub flags: StyleFlags,
+}
+
+/// The styles of all log levels, picking the best color the terminal supports
+/// the default theme prints `TRACE` dim gray, `DEBUG` blue, `INFO` green, `WARN` yellow and
+/// `ERROR` bold red
+///
+/// Example
+/// ```
+/// use terminal_color_builder::logging::LogTheme;
+/// use terminal_color_builder::mode::ColorSupport;
+/// # #[cfg(feature = "std")] {
+/// let tag = LogTheme::default().styled_level(log::Level::Warn, &ColorSupport::detect());
+/// println!("{} disk almost full", tag.print());
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogTheme {
+    pub error: LevelStyle,
+    pub warn: LevelStyle,
+    pub info: LevelStyle,
+    pub debug: LevelStyle,
+    pub trace: LevelStyle,
+}
+
+impl Default for LogTheme {
+    fn default() -> Self {
+        let level = |rgb, ansi256, basic, flags| LevelStyle {
+            rgb,
+            ansi256,
+            basic,
+            flags,
+        };
+        LogTheme {
+            error: level((215, 0, 0), 160, COLORS::Red, StyleFlags::BOLD),
+            warn: level((215, 175, 0), 178, COLORS::Yellow, StyleFlags::empty()),
+            info: level((95, 175, 95), 71, COLORS::Green, StyleFlags::empty()),
+            debug: level((95, 135, 255), 69, COLORS::Blue, StyleFlags::empty()),
+            trace: level((128, 128, 128), 244, COLORS::White, StyleFlags::DIM),
+        }
+    }
+}
+
+/// A theme taking the level colors from the slots of the palette, errors are bold and trace
+/// messages dim like in the default theme. The fallback colors are the nearest palette entries
+///
+/// Example
+/// ```
+/// use terminal_color_builder::logging::LogTheme;
+/// use terminal_color_builder::palette::Palette;
+/// let theme = LogTheme::from(&Palette::nord());
+/// assert_eq!((0xbf, 0x61, 0x6a), theme.error.rgb);
+/// ```
+#[cfg(feature = "truecolor")]
+impl From<&Palette> for LogTheme {
+    fn from(palette: &Palette) -> Self {
+        let level = |color: COLORS, flags| {
+            let rgb = Color::to_rgb(&color).unwrap_or((0, 0, 0));
+            LevelStyle {
+                rgb,
+                ansi256: Color::rgb_to_ansi256(rgb.0, rgb.1, rgb.2),
+                basic: Color::rgb_to_basic(rgb.0, rgb.1, rgb.2),
+                flags,
+            }
+        };
+        LogTheme {
+            error: level(palette.error(), StyleFlags::BOLD),
+            warn: level(palette.warning(), StyleFlags::empty()),
+            info: level(palette.success(), StyleFlags::empty()),
+            debug: level(palette.accent(), StyleFlags::empty()),
+            trace: level(palette.foreground(), StyleFlags::DIM),
+        }
+    }
+}
+
+impl LogTheme {
+    /// the style of the log level
+    pub fn level_style(&self, level: Level) -> &LevelStyle {
+        match level {
+            Level::Error => &self.error,
+            Level::Warn => &self.warn,
+            Level::Info => &self.info,
+            Level::Debug => &self.debug,
+            Level::Trace => &self.trace,
+        }
+    }
+
+    /// the name of the log level in its style, with the best color the terminal supports
+    /// without any color support only the name is left
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::logging::LogTheme;
+    /// use terminal_color_builder::mode::ColorSupport;
+    /// let support = ColorSupport { enabled: true, basic: true, ansi88: true, ansi256: true, truecolor: false };
+    /// let tag = LogTheme::default().styled_level(log::Level::Info, &support);
+    /// assert_eq!("\u{1b}[38;5;71mINFO\u{1b}[39;49m", tag.print());
+    /// ```
+    pub fn styled_level(&self, level: Level, support: &ColorSupport) -> OutputFormatter {
+        let style = self.level_style(level);
+        let (r, g, b) = style.rgb;
+        let color = match support.mode() {
+            #[cfg(feature = "truecolor")]
+            ColorMode::TrueColor => COLORS::Rgb(r, g, b),
+            // without 24-bit colors in the build the 256-color palette is the best there is
+            #[cfg(not(feature = "truecolor"))]
+            ColorMode::TrueColor => COLORS::Ansi256(style.ansi256),
+            ColorMode::Ansi256 => COLORS::Ansi256(style.ansi256),
+            ColorMode::Ansi88 => COLORS::Ansi256(Color::rgb_to_ansi88(r, g, b)),
+            ColorMode::Basic => style.basic.clone(),
+            _ => return OutputFormatter::new().text_str(level.as_str()),
+        };
+        OutputFormatter::new().fg_styled(color, style.flags).text_str(level.as_str())
+    }
+}
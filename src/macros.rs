@@ -0,0 +1,68 @@
+/// Build a colored string from a list of segments
+/// every segment is a list of color and style keywords, followed by its text.
+/// Colors apply to the foreground, prefix them with `on_` for the background.
+/// Segments don't share their colors, a segment without keywords is printed uncolored
+///
+/// Example
+/// ```
+/// use terminal_color_builder::color;
+/// let s = color!(red bold "error: ", default "details");
+/// let plain = color!("no colors");
+/// ```
+#[macro_export]
+macro_rules! color {
+    (@apply $builder:expr,) => { $builder };
+    (@apply $builder:expr, black $($rest:ident)*) => { $crate::color!(@apply $builder.fg().black(), $($rest)*) };
+    (@apply $builder:expr, red $($rest:ident)*) => { $crate::color!(@apply $builder.fg().red(), $($rest)*) };
+    (@apply $builder:expr, green $($rest:ident)*) => { $crate::color!(@apply $builder.fg().green(), $($rest)*) };
+    (@apply $builder:expr, yellow $($rest:ident)*) => { $crate::color!(@apply $builder.fg().yellow(), $($rest)*) };
+    (@apply $builder:expr, blue $($rest:ident)*) => { $crate::color!(@apply $builder.fg().blue(), $($rest)*) };
+    (@apply $builder:expr, magenta $($rest:ident)*) => { $crate::color!(@apply $builder.fg().magenta(), $($rest)*) };
+    (@apply $builder:expr, cyan $($rest:ident)*) => { $crate::color!(@apply $builder.fg().cyan(), $($rest)*) };
+    (@apply $builder:expr, white $($rest:ident)*) => { $crate::color!(@apply $builder.fg().white(), $($rest)*) };
+    (@apply $builder:expr, default $($rest:ident)*) => { $crate::color!(@apply $builder.fg().default_color(), $($rest)*) };
+    (@apply $builder:expr, on_black $($rest:ident)*) => { $crate::color!(@apply $builder.bg().black(), $($rest)*) };
+    (@apply $builder:expr, on_red $($rest:ident)*) => { $crate::color!(@apply $builder.bg().red(), $($rest)*) };
+    (@apply $builder:expr, on_green $($rest:ident)*) => { $crate::color!(@apply $builder.bg().green(), $($rest)*) };
+    (@apply $builder:expr, on_yellow $($rest:ident)*) => { $crate::color!(@apply $builder.bg().yellow(), $($rest)*) };
+    (@apply $builder:expr, on_blue $($rest:ident)*) => { $crate::color!(@apply $builder.bg().blue(), $($rest)*) };
+    (@apply $builder:expr, on_magenta $($rest:ident)*) => { $crate::color!(@apply $builder.bg().magenta(), $($rest)*) };
+    (@apply $builder:expr, on_cyan $($rest:ident)*) => { $crate::color!(@apply $builder.bg().cyan(), $($rest)*) };
+    (@apply $builder:expr, on_white $($rest:ident)*) => { $crate::color!(@apply $builder.bg().white(), $($rest)*) };
+    (@apply $builder:expr, on_default $($rest:ident)*) => { $crate::color!(@apply $builder.bg().default_color(), $($rest)*) };
+    (@apply $builder:expr, bold $($rest:ident)*) => { $crate::color!(@apply $builder.bold(), $($rest)*) };
+    (@apply $builder:expr, dim $($rest:ident)*) => { $crate::color!(@apply $builder.dim(), $($rest)*) };
+    (@apply $builder:expr, italic $($rest:ident)*) => { $crate::color!(@apply $builder.italic(), $($rest)*) };
+    (@apply $builder:expr, underline $($rest:ident)*) => { $crate::color!(@apply $builder.underline(), $($rest)*) };
+    (@apply $builder:expr, blink $($rest:ident)*) => { $crate::color!(@apply $builder.blink(), $($rest)*) };
+    (@apply $builder:expr, reverse $($rest:ident)*) => { $crate::color!(@apply $builder.reverse(), $($rest)*) };
+    (@apply $builder:expr, hidden $($rest:ident)*) => { $crate::color!(@apply $builder.hidden(), $($rest)*) };
+    (@apply $builder:expr, strikethrough $($rest:ident)*) => { $crate::color!(@apply $builder.strikethrough(), $($rest)*) };
+    (@build $($keyword:ident)* $text:literal $(, $($rest_keyword:ident)* $rest_text:literal)* $(,)?) => {{
+        let builder = $crate::color!(@apply $crate::OutputFormatter::new(), $($keyword)*).text_str($text);
+        $(
+            let builder = $crate::color!(@apply builder.reset(), $($rest_keyword)*).text_str($rest_text);
+        )*
+        builder
+    }};
+    ($($segments:tt)*) => {
+        $crate::color!(@build $($segments)*).print()
+    };
+}
+
+/// Write colored segments to an `std::io::Write`, the segments are the same as for `color!`
+/// returns the `io::Result` of writing
+///
+/// Example
+/// ```
+/// use terminal_color_builder::write_colored;
+/// let mut out: Vec<u8> = vec![];
+/// write_colored!(out, red bold "error: ", default "details").unwrap();
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! write_colored {
+    ($w:expr, $($segments:tt)*) => {
+        $crate::color!(@build $($segments)*).write_to(&mut $w)
+    };
+}
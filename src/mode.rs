@@ -0,0 +1,164 @@
+use crate::color::{Color, COLORS};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// How many colors the output may use, see `OutputFormatter::render`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 24-bit colors, the same output as `print()`
+    TrueColor,
+    /// the 256-color palette, rgb colors are mapped to the nearest palette entry
+    Ansi256,
+    /// the 88-color palette of rxvt, rgb and 256-palette colors are mapped to the nearest entry
+    Ansi88,
+    /// the eight base colors, every other color is mapped to the nearest of them
+    Basic,
+    /// no escape sequences at all, only the text
+    None,
+    /// no colors or styles, but hyperlinks are kept, for pagers and screen readers that can
+    /// follow links but not show colors
+    NoneKeepLinks,
+    /// pick the mode from the environment, see `ColorSupport::detect`
+    /// `NO_COLOR` wins over `CLICOLOR_FORCE`, which wins over the terminal check and `CLICOLOR`
+    /// the environment is only looked at once, the result is reused by all following renders
+    /// without `std` there is no environment to look at, so this renders like `TrueColor`
+    Auto,
+}
+
+impl ColorMode {
+    /// the mode to render in, `Auto` is replaced by the detected mode
+    /// @internal
+    pub(crate) fn resolve(self) -> ColorMode {
+        match self {
+            #[cfg(feature = "std")]
+            ColorMode::Auto => {
+                static SUPPORT: std::sync::OnceLock<ColorSupport> = std::sync::OnceLock::new();
+                SUPPORT.get_or_init(ColorSupport::detect).mode()
+            },
+            #[cfg(not(feature = "std"))]
+            ColorMode::Auto => ColorMode::TrueColor,
+            mode => mode,
+        }
+    }
+
+    /// converts a single SGR code to a color this mode can show, other codes are kept as they are
+    /// @internal
+    pub(crate) fn convert(&self, code: &str) -> String {
+        let is_fg = Color::is_fg_code(code);
+        if !is_fg && !Color::is_bg_code(code) {
+            return code.into();
+        }
+        let prefix = &code[..1];
+        let params: Vec<&str> = code[1..].split(';').collect();
+        let rgb = match params.as_slice() {
+            ["8", "2", r, g, b] => match (r.parse(), g.parse(), b.parse()) {
+                (Ok(r), Ok(g), Ok(b)) => (r, g, b),
+                _ => return code.into(),
+            },
+            ["8", "5", n] if *self == ColorMode::Basic || *self == ColorMode::Ansi88 => match n.parse::<u8>() {
+                // the 16 base entries exist in every palette, they keep their color instead of
+                // being matched by rgb value
+                Ok(n) if n < 16 && *self == ColorMode::Ansi88 => return code.into(),
+                Ok(n) if n < 16 => return format!("{}{}", prefix, n % 8),
+                Ok(n) => Color::ansi256_to_rgb(n),
+                _ => return code.into(),
+            },
+            _ => return code.into(),
+        };
+        match self {
+            ColorMode::Ansi256 => format!("{}8;5;{}", prefix, Color::rgb_to_ansi256(rgb.0, rgb.1, rgb.2)),
+            ColorMode::Ansi88 => format!("{}8;5;{}", prefix, Color::rgb_to_ansi88(rgb.0, rgb.1, rgb.2)),
+            ColorMode::Basic => {
+                let color = Color::rgb_to_basic(rgb.0, rgb.1, rgb.2);
+                let color = if is_fg {
+                    Color::new(color, COLORS::None)
+                } else {
+                    Color::new(COLORS::None, color)
+                };
+                color.apply().concat()
+            },
+            _ => code.into(),
+        }
+    }
+}
+
+/// The colors the terminal supports, detected from the environment
+///
+/// Example
+/// ```
+/// use terminal_color_builder::mode::ColorSupport;
+/// # #[cfg(feature = "std")] {
+/// let support = ColorSupport::detect();
+/// if support.truecolor {
+///     println!("24-bit colors are available");
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorSupport {
+    /// colors are shown at all
+    pub enabled: bool,
+    /// the eight base colors
+    pub basic: bool,
+    /// the 88-color palette
+    pub ansi88: bool,
+    /// the 256-color palette
+    pub ansi256: bool,
+    /// 24-bit colors
+    pub truecolor: bool,
+}
+
+impl ColorSupport {
+    /// looks at `NO_COLOR`, `CLICOLOR_FORCE`, `CLICOLOR`, `TERM`, `COLORTERM` and whether stdout is
+    /// a terminal. `NO_COLOR` disables colors, `CLICOLOR_FORCE` enables them even without a terminal,
+    /// `CLICOLOR=0`, `TERM=dumb` or no `TERM` at all disable them otherwise
+    #[cfg(feature = "std")]
+    pub fn detect() -> ColorSupport {
+        use std::io::IsTerminal;
+        ColorSupport::detect_from(|name| std::env::var(name).ok(), std::io::stdout().is_terminal())
+    }
+
+    /// detects the support from environment variables looked up with `var`
+    /// @internal
+    #[cfg(any(feature = "std", test))]
+    pub(crate) fn detect_from<F>(var: F, is_tty: bool) -> ColorSupport
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let is_set = |name: &str| var(name).is_some_and(|v| !v.is_empty() && v != "0");
+        let term = var("TERM");
+        let enabled = if var("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            false
+        } else if is_set("CLICOLOR_FORCE") {
+            true
+        } else {
+            // a dumb terminal, or no terminal type at all, cannot show escape sequences
+            is_tty && var("CLICOLOR").as_deref() != Some("0") && !matches!(term.as_deref(), None | Some("dumb"))
+        };
+        let truecolor = enabled && matches!(var("COLORTERM").as_deref(), Some("truecolor") | Some("24bit"));
+        let ansi256 = truecolor || (enabled && term.as_deref().is_some_and(|t| t.contains("256color")));
+        ColorSupport {
+            enabled,
+            basic: enabled,
+            ansi88: ansi256 || (enabled && term.as_deref().is_some_and(|t| t.contains("88color"))),
+            ansi256,
+            truecolor,
+        }
+    }
+
+    /// the best mode the terminal supports
+    pub fn mode(&self) -> ColorMode {
+        if self.truecolor {
+            ColorMode::TrueColor
+        } else if self.ansi256 {
+            ColorMode::Ansi256
+        } else if self.ansi88 {
+            ColorMode::Ansi88
+        } else if self.basic {
+            ColorMode::Basic
+        } else {
+            ColorMode::None
+        }
+    }
+}
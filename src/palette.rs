@@ -0,0 +1,111 @@
+use crate::color::COLORS;
+
+/// A set of colors for semantic slots, matching popular terminal themes
+///
+/// Example
+/// ```
+/// use terminal_color_builder::*;
+/// use terminal_color_builder::palette::Palette;
+/// let p = Palette::dracula();
+/// let c = OutputFormatter::new().custom(p.error(), p.base()).text_str("failed");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    base: (u8, u8, u8),
+    foreground: (u8, u8, u8),
+    accent: (u8, u8, u8),
+    error: (u8, u8, u8),
+    warning: (u8, u8, u8),
+    success: (u8, u8, u8),
+}
+
+impl Palette {
+    /// create a custom palette, the slots are given as rgb values
+    pub fn new(
+        base: (u8, u8, u8),
+        foreground: (u8, u8, u8),
+        accent: (u8, u8, u8),
+        error: (u8, u8, u8),
+        warning: (u8, u8, u8),
+        success: (u8, u8, u8),
+    ) -> Self {
+        Palette {
+            base,
+            foreground,
+            accent,
+            error,
+            warning,
+            success,
+        }
+    }
+
+    /// the dark variant of Solarized
+    pub fn solarized() -> Self {
+        Palette::new(
+            (0x00, 0x2b, 0x36),
+            (0x83, 0x94, 0x96),
+            (0x26, 0x8b, 0xd2),
+            (0xdc, 0x32, 0x2f),
+            (0xb5, 0x89, 0x00),
+            (0x85, 0x99, 0x00),
+        )
+    }
+
+    /// Dracula
+    pub fn dracula() -> Self {
+        Palette::new(
+            (0x28, 0x2a, 0x36),
+            (0xf8, 0xf8, 0xf2),
+            (0xbd, 0x93, 0xf9),
+            (0xff, 0x55, 0x55),
+            (0xf1, 0xfa, 0x8c),
+            (0x50, 0xfa, 0x7b),
+        )
+    }
+
+    /// Nord
+    pub fn nord() -> Self {
+        Palette::new(
+            (0x2e, 0x34, 0x40),
+            (0xd8, 0xde, 0xe9),
+            (0x88, 0xc0, 0xd0),
+            (0xbf, 0x61, 0x6a),
+            (0xeb, 0xcb, 0x8b),
+            (0xa3, 0xbe, 0x8c),
+        )
+    }
+
+    /// background color
+    pub fn base(&self) -> COLORS {
+        Palette::color(self.base)
+    }
+
+    /// regular text color
+    pub fn foreground(&self) -> COLORS {
+        Palette::color(self.foreground)
+    }
+
+    /// color for highlights
+    pub fn accent(&self) -> COLORS {
+        Palette::color(self.accent)
+    }
+
+    /// color for errors
+    pub fn error(&self) -> COLORS {
+        Palette::color(self.error)
+    }
+
+    /// color for warnings
+    pub fn warning(&self) -> COLORS {
+        Palette::color(self.warning)
+    }
+
+    /// color for success messages
+    pub fn success(&self) -> COLORS {
+        Palette::color(self.success)
+    }
+
+    fn color((r, g, b): (u8, u8, u8)) -> COLORS {
+        COLORS::Rgb(r, g, b)
+    }
+}
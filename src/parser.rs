@@ -0,0 +1,108 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// splits a string containing ANSI escape sequences into the entries of an `OutputFormatter`
+/// @internal
+pub(crate) fn parse(s: &str) -> Vec<Vec<String>> {
+    let mut output: Vec<Vec<String>> = vec![];
+    let mut text = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find('\x1b') {
+        text.push_str(&rest[..start]);
+        let sequence = &rest[start..];
+        let len = match sequence_len(sequence) {
+            Some(len) => len,
+            None => {
+                // a lone escape character is not a sequence, keep it as text
+                text.push('\x1b');
+                rest = &sequence[1..];
+                continue;
+            }
+        };
+        if !text.is_empty() {
+            output.push(vec!["#text#".to_string(), text]);
+            text = String::new();
+        }
+        match sgr_params(&sequence[..len]) {
+            Some(params) => output.push(group_codes(params)),
+            None => output.push(vec!["#raw#".to_string(), sequence[..len].to_string()]),
+        }
+        rest = &sequence[len..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        output.push(vec!["#text#".to_string(), text]);
+    }
+    output
+}
+
+/// removes every escape sequence, keeping only the text
+/// @internal
+pub(crate) fn strip(s: &str) -> String {
+    let mut text = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find('\x1b') {
+        text.push_str(&rest[..start]);
+        let sequence = &rest[start..];
+        let len = sequence_len(sequence).unwrap_or_else(|| {
+            text.push('\x1b');
+            1
+        });
+        rest = &sequence[len..];
+    }
+    text.push_str(rest);
+    text
+}
+
+/// length of the escape sequence at the start of the string
+fn sequence_len(sequence: &str) -> Option<usize> {
+    let bytes = sequence.as_bytes();
+    match bytes.get(1) {
+        // CSI, ends with the first byte in the range 0x40-0x7e
+        Some(b'[') => bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b)).map(|p| p + 3),
+        // OSC, ends with BEL or ESC \
+        Some(b']') => {
+            for i in 2..bytes.len() {
+                if bytes[i] == 0x07 {
+                    return Some(i + 1);
+                }
+                if bytes[i] == 0x1b && bytes.get(i + 1) == Some(&b'\\') {
+                    return Some(i + 2);
+                }
+            }
+            None
+        },
+        Some(b) if (0x40..=0x5f).contains(b) => Some(2),
+        _ => None,
+    }
+}
+
+/// the parameters of an SGR sequence, `None` for any other sequence
+fn sgr_params(sequence: &str) -> Option<&str> {
+    let params = sequence.strip_prefix("\x1b[")?.strip_suffix('m')?;
+    if params.chars().all(|c| c.is_ascii_digit() || c == ';') {
+        Some(params)
+    } else {
+        None
+    }
+}
+
+/// groups SGR parameters into codes, keeping extended colors like `38;2;r;g;b` together
+fn group_codes(params: &str) -> Vec<String> {
+    let parts: Vec<&str> = params.split(';').collect();
+    let mut codes: Vec<String> = vec![];
+    let mut i = 0;
+    while i < parts.len() {
+        let len = match (parts[i], parts.get(i + 1)) {
+            ("38", Some(&"2")) | ("48", Some(&"2")) => 5,
+            ("38", Some(&"5")) | ("48", Some(&"5")) => 3,
+            _ => 1,
+        };
+        let len = len.min(parts.len() - i);
+        let code = parts[i..i + len].join(";");
+        codes.push(if code.is_empty() { "0".to_string() } else { code });
+        i += len;
+    }
+    codes
+}
@@ -0,0 +1,242 @@
+use crate::color::{Color, COLORS};
+use crate::style::Style;
+use crate::{Entry, OutputFormatter};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Turns the builder into an output format
+pub trait Renderer {
+    fn render(&self, formatter: &OutputFormatter) -> String;
+}
+
+/// Renders ANSI escape sequences, the same as `OutputFormatter::print`
+pub struct AnsiRenderer;
+
+impl Renderer for AnsiRenderer {
+    fn render(&self, formatter: &OutputFormatter) -> String {
+        formatter.print()
+    }
+}
+
+/// Renders Markdown, bold, italic, strikethrough and links are kept, colors are dropped
+///
+/// Example
+/// ```
+/// use terminal_color_builder::*;
+/// use terminal_color_builder::render::MarkdownRenderer;
+/// let c = OutputFormatter::new().bold().text_str("important");
+/// assert_eq!("**important**", c.render_with(&MarkdownRenderer));
+/// ```
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn render(&self, formatter: &OutputFormatter) -> String {
+        let mut message = String::new();
+        for segment in merge_equal(decode(formatter), |a, b| a.styles == b.styles && a.link == b.link) {
+            let mut markers = String::new();
+            if segment.styles.contains(&Style::Bold) {
+                markers.push_str("**");
+            }
+            if segment.styles.contains(&Style::Italic) {
+                markers.push('*');
+            }
+            if segment.styles.contains(&Style::Strikethrough) {
+                markers.push_str("~~");
+            }
+            let closing: String = markers.chars().rev().collect();
+            let text = format_markdown(&segment.text);
+            let text = format!("{}{}{}", markers, text, closing);
+            match segment.link {
+                Some(url) => message.push_str(&format!("[{}]({})", text, url)),
+                None => message.push_str(&text),
+            }
+        }
+        message
+    }
+}
+
+/// Renders HTML, every text with its own colors and styles becomes a `<span>` with inline css
+/// unset colors are inherited from the page, `COLORS::Transparent` is made explicitly transparent
+///
+/// Example
+/// ```
+/// use terminal_color_builder::*;
+/// use terminal_color_builder::render::HtmlRenderer;
+/// let c = OutputFormatter::new().fg().red().bold().text_str("a < b");
+/// assert_eq!(
+///     "<span style=\"color: #800000; font-weight: bold\">a &lt; b</span>",
+///     c.render_with(&HtmlRenderer)
+/// );
+/// ```
+pub struct HtmlRenderer;
+
+impl Renderer for HtmlRenderer {
+    fn render(&self, formatter: &OutputFormatter) -> String {
+        let mut html = String::new();
+        let segments = merge_equal(decode(formatter), |a, b| {
+            a.fg == b.fg && a.bg == b.bg && a.styles == b.styles && a.link == b.link
+        });
+        for segment in segments {
+            let mut css: Vec<String> = vec![];
+            let (fg, bg) = Color::new(segment.fg_or_none(), segment.bg_or_none()).to_css();
+            if let Some(color) = fg {
+                css.push(format!("color: {}", color));
+            }
+            if let Some(color) = bg {
+                css.push(format!("background: {}", color));
+            }
+            let has = |style: Style| segment.styles.contains(&style);
+            if has(Style::Bold) {
+                css.push("font-weight: bold".to_string());
+            }
+            if has(Style::Dim) {
+                css.push("opacity: 0.5".to_string());
+            }
+            if has(Style::Italic) {
+                css.push("font-style: italic".to_string());
+            }
+            let mut decorations: Vec<&str> = vec![];
+            if has(Style::Underline) || has(Style::DoubleUnderline) {
+                decorations.push("underline");
+            }
+            if has(Style::Strikethrough) {
+                decorations.push("line-through");
+            }
+            if has(Style::Overline) {
+                decorations.push("overline");
+            }
+            if !decorations.is_empty() {
+                css.push(format!("text-decoration: {}", decorations.join(" ")));
+            }
+            if has(Style::Hidden) {
+                css.push("visibility: hidden".to_string());
+            }
+            let mut text = format_html(&segment.text);
+            if !css.is_empty() {
+                text = format!("<span style=\"{}\">{}</span>", css.join("; "), text);
+            }
+            match segment.link {
+                Some(url) => html.push_str(&format!("<a href=\"{}\">{}</a>", format_html(&url), text)),
+                None => html.push_str(&text),
+            }
+        }
+        html
+    }
+}
+
+/// A text with the colors and styles active for it, see `OutputFormatter::to_segments`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedSegment {
+    /// the foreground color, `None` if no color was set
+    pub fg: Option<COLORS>,
+    /// the background color, `None` if no color was set
+    pub bg: Option<COLORS>,
+    pub styles: Vec<Style>,
+    /// the url the text links to
+    pub link: Option<String>,
+    pub text: String,
+}
+
+impl RenderedSegment {
+    /// the foreground, `COLORS::None` if no color was set
+    /// @internal
+    pub(crate) fn fg_or_none(&self) -> COLORS {
+        self.fg.clone().unwrap_or(COLORS::None)
+    }
+
+    /// the background, `COLORS::None` if no color was set
+    /// @internal
+    pub(crate) fn bg_or_none(&self) -> COLORS {
+        self.bg.clone().unwrap_or(COLORS::None)
+    }
+}
+
+/// resolves the codes of the builder into the state active for each text
+/// @internal
+pub(crate) fn decode(formatter: &OutputFormatter) -> Vec<RenderedSegment> {
+    let mut segments: Vec<RenderedSegment> = vec![];
+    let mut styles: Vec<Style> = vec![];
+    let mut link: Option<String> = None;
+    let (mut fg, mut bg): (Option<COLORS>, Option<COLORS>) = (None, None);
+    for entry in formatter.entries() {
+        match entry {
+            Entry::Codes(codes) => {
+                for code in codes {
+                    if code == "0" {
+                        styles.clear();
+                        fg = None;
+                        bg = None;
+                    } else if Color::is_fg_code(code) || Color::is_bg_code(code) {
+                        let color = Color::color_from_code(code);
+                        if Color::is_fg_code(code) {
+                            fg = color;
+                        } else {
+                            bg = color;
+                        }
+                    } else if let Some(style) = Style::from_on_code(code) {
+                        if !styles.contains(&style) {
+                            styles.push(style);
+                        }
+                    } else {
+                        styles.retain(|s| s.off_code() != *code);
+                    }
+                }
+            },
+            Entry::Text(text) => segments.push(RenderedSegment {
+                fg: fg.clone(),
+                bg: bg.clone(),
+                styles: styles.clone(),
+                link: link.take(),
+                text: text.to_string(),
+            }),
+            Entry::Link(url) => link = Some(url.to_string()),
+            Entry::Transparent(true) => bg = Some(COLORS::Transparent),
+            Entry::Transparent(false) => fg = Some(COLORS::Transparent),
+            Entry::Raw(_) => {},
+        }
+    }
+    segments
+}
+
+/// joins neighbouring segments the renderer would print the same way
+fn merge_equal<F>(segments: Vec<RenderedSegment>, equal: F) -> Vec<RenderedSegment>
+where
+    F: Fn(&RenderedSegment, &RenderedSegment) -> bool,
+{
+    let mut merged: Vec<RenderedSegment> = vec![];
+    for segment in segments {
+        match merged.last_mut() {
+            Some(last) if equal(last, &segment) && segment.link.is_none() => last.text.push_str(&segment.text),
+            _ => merged.push(segment),
+        }
+    }
+    merged
+}
+
+fn format_markdown(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if "\\*_[]`~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn format_html(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,176 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use bitflags::bitflags;
+
+/// Text attributes that can be combined with colors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Bold,
+    Dim,
+    Italic,
+    Underline,
+    Blink,
+    BlinkRapid,
+    Reverse,
+    Hidden,
+    Strikethrough,
+    DoubleUnderline,
+    Overline,
+    Framed,
+    Encircled,
+}
+
+bitflags! {
+    /// A set of styles, used to apply several styles at once
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::*;
+    /// use terminal_color_builder::style::StyleFlags;
+    /// let c = OutputFormatter::new().style(StyleFlags::BOLD | StyleFlags::UNDERLINE).text_str("Hi");
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct StyleFlags: u16 {
+        const BOLD = 1 << 0;
+        const DIM = 1 << 1;
+        const ITALIC = 1 << 2;
+        const UNDERLINE = 1 << 3;
+        const BLINK = 1 << 4;
+        const BLINK_RAPID = 1 << 5;
+        const REVERSE = 1 << 6;
+        const HIDDEN = 1 << 7;
+        const STRIKETHROUGH = 1 << 8;
+        const DOUBLE_UNDERLINE = 1 << 9;
+        const OVERLINE = 1 << 10;
+        const FRAMED = 1 << 11;
+        const ENCIRCLED = 1 << 12;
+    }
+}
+
+impl Style {
+    /// every style, in the order their codes are emitted
+    pub const ALL: [Style; 13] = [
+        Style::Bold,
+        Style::Dim,
+        Style::Italic,
+        Style::Underline,
+        Style::Blink,
+        Style::BlinkRapid,
+        Style::Reverse,
+        Style::Hidden,
+        Style::Strikethrough,
+        Style::DoubleUnderline,
+        Style::Overline,
+        Style::Framed,
+        Style::Encircled,
+    ];
+
+    /// the flag representing this style in a `StyleFlags` set
+    pub fn flag(&self) -> StyleFlags {
+        match self {
+            Style::Bold => StyleFlags::BOLD,
+            Style::Dim => StyleFlags::DIM,
+            Style::Italic => StyleFlags::ITALIC,
+            Style::Underline => StyleFlags::UNDERLINE,
+            Style::Blink => StyleFlags::BLINK,
+            Style::BlinkRapid => StyleFlags::BLINK_RAPID,
+            Style::Reverse => StyleFlags::REVERSE,
+            Style::Hidden => StyleFlags::HIDDEN,
+            Style::Strikethrough => StyleFlags::STRIKETHROUGH,
+            Style::DoubleUnderline => StyleFlags::DOUBLE_UNDERLINE,
+            Style::Overline => StyleFlags::OVERLINE,
+            Style::Framed => StyleFlags::FRAMED,
+            Style::Encircled => StyleFlags::ENCIRCLED,
+        }
+    }
+
+    /// the SGR parameter switching the style on
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::style::Style;
+    /// assert_eq!(1, Style::Bold.sgr_on());
+    /// ```
+    pub fn sgr_on(&self) -> u16 {
+        match self {
+            Style::Bold => 1,
+            Style::Dim => 2,
+            Style::Italic => 3,
+            Style::Underline => 4,
+            Style::Blink => 5,
+            Style::BlinkRapid => 6,
+            Style::Reverse => 7,
+            Style::Hidden => 8,
+            Style::Strikethrough => 9,
+            Style::DoubleUnderline => 21,
+            Style::Overline => 53,
+            Style::Framed => 51,
+            Style::Encircled => 52,
+        }
+    }
+
+    /// the SGR parameter switching the style off again, some styles share it
+    /// (e.g. `22` switches off both bold and dim)
+    ///
+    /// Example
+    /// ```
+    /// use terminal_color_builder::style::Style;
+    /// assert_eq!(22, Style::Bold.sgr_off());
+    /// assert_eq!(Style::Bold.sgr_off(), Style::Dim.sgr_off());
+    /// ```
+    pub fn sgr_off(&self) -> u16 {
+        match self {
+            Style::Bold | Style::Dim => 22,
+            Style::Italic => 23,
+            Style::Underline | Style::DoubleUnderline => 24,
+            Style::Blink | Style::BlinkRapid => 25,
+            Style::Reverse => 27,
+            Style::Hidden => 28,
+            Style::Strikethrough => 29,
+            Style::Overline => 55,
+            Style::Framed | Style::Encircled => 54,
+        }
+    }
+
+    /// the SGR code switching the style on, see `sgr_on`
+    /// @internal
+    pub(crate) fn on_code(&self) -> String {
+        self.sgr_on().to_string()
+    }
+
+    /// the SGR code switching the style off again, see `sgr_off`
+    /// @internal
+    pub(crate) fn off_code(&self) -> String {
+        self.sgr_off().to_string()
+    }
+
+    /// the codes switching off the given styles, every code only once
+    /// @internal
+    pub(crate) fn off_codes<'a, I>(styles: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = &'a Style>,
+    {
+        let mut codes: Vec<String> = vec![];
+        for style in styles {
+            let off = style.off_code();
+            if !codes.contains(&off) {
+                codes.push(off);
+            }
+        }
+        codes
+    }
+
+    /// finds the style that is switched on by the given SGR code
+    /// @internal
+    pub(crate) fn from_on_code(code: &str) -> Option<Style> {
+        Style::ALL.iter().copied().find(|s| s.on_code() == code)
+    }
+}
+
+impl StyleFlags {
+    /// the single styles contained in this set
+    pub fn styles(&self) -> impl Iterator<Item = Style> + '_ {
+        Style::ALL.iter().copied().filter(move |s| self.contains(s.flag()))
+    }
+}
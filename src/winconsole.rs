@@ -0,0 +1,154 @@
+use crate::color::{Color, COLORS};
+use crate::render::{decode, RenderedSegment};
+use crate::style::Style;
+use crate::OutputFormatter;
+use core::ffi::c_void;
+use std::io::{self, Write};
+
+const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+const FOREGROUND_BLUE: u16 = 0x1;
+const FOREGROUND_GREEN: u16 = 0x2;
+const FOREGROUND_RED: u16 = 0x4;
+const FOREGROUND_INTENSITY: u16 = 0x8;
+
+#[repr(C)]
+struct Coord {
+    x: i16,
+    y: i16,
+}
+
+#[repr(C)]
+struct SmallRect {
+    left: i16,
+    top: i16,
+    right: i16,
+    bottom: i16,
+}
+
+#[repr(C)]
+struct ConsoleScreenBufferInfo {
+    size: Coord,
+    cursor_position: Coord,
+    attributes: u16,
+    window: SmallRect,
+    maximum_window_size: Coord,
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetStdHandle(std_handle: u32) -> *mut c_void;
+    fn GetConsoleScreenBufferInfo(console: *mut c_void, info: *mut ConsoleScreenBufferInfo) -> i32;
+    fn SetConsoleTextAttribute(console: *mut c_void, attributes: u16) -> i32;
+}
+
+/// Writes the builder to a legacy Windows console, for consoles that cannot show escape sequences at all
+/// every color is replaced by the nearest of the 16 console colors and set with `SetConsoleTextAttribute`,
+/// bold makes the foreground bright, links and other escape sequences are dropped
+///
+/// Example
+/// ```no_run
+/// use terminal_color_builder::*;
+/// use terminal_color_builder::winconsole::ConsoleWriter;
+/// if let Some(w) = ConsoleWriter::stdout() {
+///     w.write(&OutputFormatter::new().fg().red().text_str("failed")).unwrap();
+/// }
+/// ```
+pub struct ConsoleWriter {
+    handle: *mut c_void,
+    default: u16,
+}
+
+impl ConsoleWriter {
+    /// a writer for stdout, `None` if stdout is not a console, e.g. when it is redirected to a file
+    pub fn stdout() -> Option<ConsoleWriter> {
+        let handle = unsafe { GetStdHandle(STD_OUTPUT_HANDLE) };
+        // an invalid handle is -1, a process without stdout gets a null handle
+        if handle.is_null() || handle as isize == -1 {
+            return None;
+        }
+        let mut info = ConsoleScreenBufferInfo {
+            size: Coord { x: 0, y: 0 },
+            cursor_position: Coord { x: 0, y: 0 },
+            attributes: 0,
+            window: SmallRect { left: 0, top: 0, right: 0, bottom: 0 },
+            maximum_window_size: Coord { x: 0, y: 0 },
+        };
+        if unsafe { GetConsoleScreenBufferInfo(handle, &mut info) } == 0 {
+            return None;
+        }
+        Some(ConsoleWriter {
+            handle,
+            default: info.attributes,
+        })
+    }
+
+    /// write the builder, the attributes the console had before are restored afterwards
+    pub fn write(&self, formatter: &OutputFormatter) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        for segment in decode(formatter) {
+            // the text written so far has to reach the console before its attributes change
+            out.flush()?;
+            self.set_attribute(ConsoleWriter::attribute(&segment, self.default))?;
+            out.write_all(segment.text.as_bytes())?;
+        }
+        out.flush()?;
+        self.set_attribute(self.default)
+    }
+
+    fn set_attribute(&self, attribute: u16) -> io::Result<()> {
+        if unsafe { SetConsoleTextAttribute(self.handle, attribute) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn attribute(segment: &RenderedSegment, default: u16) -> u16 {
+        let attribute = to_attribute(&segment.fg_or_none(), &segment.bg_or_none(), default);
+        let attribute = if segment.styles.contains(&Style::Bold) {
+            attribute | FOREGROUND_INTENSITY
+        } else {
+            attribute
+        };
+        if segment.styles.contains(&Style::Reverse) {
+            (attribute & 0xff00) | ((attribute & 0x0f) << 4) | ((attribute & 0xf0) >> 4)
+        } else {
+            attribute
+        }
+    }
+}
+
+/// the console attribute for a foreground and background color, colors are mapped to the nearest
+/// of the 16 console colors. `Default`, `None` and `Transparent` keep that part of `default`
+///
+/// Example
+/// ```
+/// use terminal_color_builder::color::COLORS;
+/// use terminal_color_builder::winconsole::to_attribute;
+/// // red on blue
+/// assert_eq!(0x14, to_attribute(&COLORS::Red, &COLORS::Blue, 0x07));
+/// ```
+pub fn to_attribute(fg: &COLORS, bg: &COLORS, default: u16) -> u16 {
+    let fg = nearest_console_color(fg).unwrap_or(default & 0x0f);
+    let bg = nearest_console_color(bg).map_or(default & 0xf0, |bg| bg << 4);
+    (default & 0xff00) | bg | fg
+}
+
+/// the console color bits of the nearest of the 16 base colors
+fn nearest_console_color(color: &COLORS) -> Option<u16> {
+    let index = match color {
+        COLORS::Default | COLORS::None | COLORS::Transparent => return None,
+        COLORS::Ansi256(n) if *n < 16 => *n,
+        _ => match Color::to_ansi256(color) {
+            Some(n) if n < 8 => n,
+            _ => (0..16u8)
+                .min_by(|a, b| {
+                    let distance = |n: u8| Color::distance(color, &COLORS::Ansi256(n));
+                    distance(*a).total_cmp(&distance(*b))
+                })
+                .unwrap_or(7),
+        },
+    };
+    // the console orders the colors blue, green, red instead of red, green, blue
+    let bits = [(1, FOREGROUND_RED), (2, FOREGROUND_GREEN), (4, FOREGROUND_BLUE), (8, FOREGROUND_INTENSITY)];
+    Some(bits.iter().filter(|(bit, _)| index & bit != 0).fold(0, |attribute, (_, flag)| attribute | flag))
+}
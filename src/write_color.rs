@@ -0,0 +1,80 @@
+#[cfg(feature = "truecolor")]
+use crate::color::Color;
+use crate::color::COLORS;
+use crate::render::{decode, RenderedSegment};
+use crate::style::Style;
+use crate::OutputFormatter;
+use std::io;
+use termcolor::{ColorSpec, WriteColor};
+
+/// Writes the builder through a `termcolor::WriteColor` target
+/// colors and styles are translated into a `ColorSpec` for each text, links and other escape sequences are dropped
+///
+/// Example
+/// ```
+/// use terminal_color_builder::*;
+/// use terminal_color_builder::write_color::ColorWriter;
+/// let mut w = ColorWriter::new(termcolor::Buffer::ansi());
+/// w.write(&OutputFormatter::new().fg().red().text_str("failed")).unwrap();
+/// let bytes = w.into_inner().into_inner();
+/// ```
+pub struct ColorWriter<W: WriteColor> {
+    target: W,
+}
+
+impl<W: WriteColor> ColorWriter<W> {
+    pub fn new(target: W) -> Self {
+        ColorWriter {
+            target,
+        }
+    }
+
+    /// write the builder, the colors are reset afterwards
+    pub fn write(&mut self, formatter: &OutputFormatter) -> io::Result<()> {
+        for segment in decode(formatter) {
+            self.target.set_color(&ColorWriter::<W>::spec(&segment))?;
+            self.target.write_all(segment.text.as_bytes())?;
+        }
+        self.target.reset()
+    }
+
+    /// the target written to
+    pub fn get_ref(&self) -> &W {
+        &self.target
+    }
+
+    /// returns the target written to
+    pub fn into_inner(self) -> W {
+        self.target
+    }
+
+    fn spec(segment: &RenderedSegment) -> ColorSpec {
+        let mut spec = ColorSpec::new();
+        spec.set_fg(to_termcolor(&segment.fg_or_none()))
+            .set_bg(to_termcolor(&segment.bg_or_none()))
+            .set_bold(segment.styles.contains(&Style::Bold))
+            .set_dimmed(segment.styles.contains(&Style::Dim))
+            .set_italic(segment.styles.contains(&Style::Italic))
+            .set_underline(segment.styles.contains(&Style::Underline))
+            .set_strikethrough(segment.styles.contains(&Style::Strikethrough));
+        spec
+    }
+}
+
+/// the termcolor color for a color of this crate, `Default` and `None` leave the color unset
+pub fn to_termcolor(color: &COLORS) -> Option<termcolor::Color> {
+    match color {
+        COLORS::Black => Some(termcolor::Color::Black),
+        COLORS::Red => Some(termcolor::Color::Red),
+        COLORS::Green => Some(termcolor::Color::Green),
+        COLORS::Yellow => Some(termcolor::Color::Yellow),
+        COLORS::Blue => Some(termcolor::Color::Blue),
+        COLORS::Magenta => Some(termcolor::Color::Magenta),
+        COLORS::Cyan => Some(termcolor::Color::Cyan),
+        COLORS::White => Some(termcolor::Color::White),
+        COLORS::Default | COLORS::None | COLORS::Transparent => None,
+        COLORS::Ansi256(n) => Some(termcolor::Color::Ansi256(*n)),
+        #[cfg(feature = "truecolor")]
+        _ => Color::to_rgb(color).map(|(r, g, b)| termcolor::Color::Rgb(r, g, b)),
+    }
+}